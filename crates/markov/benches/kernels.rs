@@ -0,0 +1,82 @@
+//! Sparse kernels on a 10k-state chain, and the induced macro kernel of a
+//! 5k-state chain seen through 200 observable values. Compare
+//! `cargo bench -p markov` with `cargo bench -p markov --features parallel`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use markov::{Markov, Matrix, Prob, Vector};
+use ndarray::linalg::Dot;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const STATES: usize = 10_000;
+const NEIGHBOURS: usize = 8;
+const MACROSTATES: usize = 100;
+
+const INDUCED_STATES: usize = 5_000;
+const INDUCED_MACROSTATES: usize = 200;
+
+// Ring with random weights to nearby states and one long-range jump per row
+fn chain(states: usize) -> Matrix<usize, usize> {
+    let mut rng = StdRng::seed_from_u64(17);
+    Matrix::from_assoc((0..states).flat_map(|x| {
+        let mut row: Vec<(usize, usize, f64)> = (0..NEIGHBOURS)
+            .map(|k| (x, (x + k) % states, rng.random::<f64>()))
+            .collect();
+        row.push((x, rng.random_range(0..states), rng.random::<f64>()));
+        row
+    }))
+}
+
+// Contiguous blocks of states
+fn observable(states: usize, macrostates: usize) -> Markov<usize, usize> {
+    let block = states / macrostates;
+    Markov::from_matrix(Matrix::from_assoc((0..states).map(|x| (x, x / block, 1.0)))).unwrap()
+}
+
+fn uniform(states: usize) -> Prob<usize> {
+    Prob::from_vector(Vector::from_assoc((0..states).map(|x| (x, 1.0)))).unwrap()
+}
+
+fn kernels(c: &mut Criterion) {
+    let matrix = chain(STATES);
+    let markov = Markov::from_matrix(matrix.clone()).unwrap();
+    let uniform = uniform(STATES);
+    let observable = observable(STATES, MACROSTATES);
+
+    c.bench_function("row normalisation", |b| {
+        b.iter(|| Markov::from_matrix(black_box(matrix.clone())).unwrap())
+    });
+
+    c.bench_function("distribution step", |b| {
+        b.iter(|| black_box(&uniform).dot(&markov))
+    });
+
+    c.bench_function("kernel times vector", |b| {
+        b.iter(|| markov.matrix.dot(black_box(&uniform.vector)))
+    });
+
+    c.bench_function("observable kernel", |b| {
+        b.iter(|| {
+            let flows = markov.matrix.map_rows(&uniform.vector, |v, p| v * p);
+            let joint = observable
+                .matrix
+                .transpose()
+                .dot(&flows)
+                .dot(&observable.matrix);
+            Markov::from_matrix(joint).unwrap()
+        })
+    });
+}
+
+fn induced_kernel(c: &mut Criterion) {
+    let markov = Markov::from_matrix(chain(INDUCED_STATES)).unwrap();
+    let observable = observable(INDUCED_STATES, INDUCED_MACROSTATES);
+    let p = uniform(INDUCED_STATES);
+
+    c.bench_function("induced kernel 5k x 200", |b| {
+        b.iter(|| markov.induced_kernel(black_box(&observable), &p).unwrap())
+    });
+}
+
+criterion_group!(benches, kernels, induced_kernel);
+criterion_main!(benches);
@@ -0,0 +1,309 @@
+use ndarray::Array1;
+use sprs::{CsMat, TriMat};
+use std::collections::VecDeque;
+
+use crate::linalg::{SolveError, SparseLu};
+use crate::markov::{BuildError, Markov};
+use crate::matrix::Matrix;
+use crate::vector::Vector;
+
+/// Absorption statistics of a chain with absorbing states.
+///
+/// With transient states T and absorbing states A, the kernel splits into
+/// Q (T × T) and R (T × A), and everything follows from the fundamental
+/// matrix N = (I − Q)⁻¹.
+#[derive(Debug, Clone)]
+pub struct Absorption<X> {
+    /// States whose only transition is to themselves.
+    pub absorbing: Vec<X>,
+    /// All other states.
+    pub transient: Vec<X>,
+    /// N = (I − Q)⁻¹: expected number of visits to the column state when
+    /// starting from the row state, over transient states.
+    pub fundamental: Matrix<X, X>,
+    /// B = N R: probability of being absorbed in the column state when
+    /// starting from the row state. Rows of absorbing states are the identity.
+    pub probabilities: Matrix<X, X>,
+    /// Expected number of steps before absorption, t = N 1 (zero for
+    /// absorbing states), over all states.
+    pub expected_steps: Vector<X>,
+    /// Variance of the number of steps before absorption, (2N − I) t − t².
+    pub steps_variance: Vector<X>,
+}
+
+impl<X> Markov<X, X>
+where
+    X: Ord + Clone,
+{
+    /// Build a kernel where states without outgoing weight are absorbing:
+    /// a self-loop is added to every such state, including target labels that
+    /// have no row in `matrix`.
+    pub fn from_matrix_with_sinks(matrix: Matrix<X, X>) -> Result<Self, BuildError> {
+        let row_sums = matrix.get_rows_sums();
+        let mut triplets: Vec<(X, X, f64)> = matrix
+            .values
+            .iter()
+            .filter_map(|(&val, (i, j))| {
+                let x = matrix.x_ix_map.value_of(i)?;
+                let y = matrix.y_ix_map.value_of(j)?;
+                Some((x.clone(), y.clone(), val))
+            })
+            .collect();
+
+        let sinks = matrix
+            .x_ix_map
+            .iter()
+            .filter(|(i, _)| row_sums.values[*i] <= 0.0)
+            .map(|(_, x)| x)
+            .chain(
+                matrix
+                    .y_ix_map
+                    .iter()
+                    .map(|(_, y)| y)
+                    .filter(|y| matrix.x_ix_map.index_of(y).is_none()),
+            );
+        for sink in sinks {
+            triplets.push((sink.clone(), sink.clone(), 1.0));
+        }
+
+        Markov::from_matrix(Matrix::from_assoc(triplets))
+    }
+
+    /// States whose only transition is to themselves.
+    pub fn absorbing_states(&self) -> Vec<X> {
+        let leaking = self.leaking_rows();
+        absorbing_indices(&self.successors())
+            .into_iter()
+            .filter(|&i| !leaking[i])
+            .filter_map(|i| self.matrix.x_ix_map.value_of(i).cloned())
+            .collect()
+    }
+
+    /// Fundamental matrix, absorption probabilities and absorption times.
+    /// Requires at least one absorbing state, reachable from every state.
+    pub fn absorption(&self) -> Result<Absorption<X>, AbsorptionError> {
+        let n = self.matrix.x_ix_map.len();
+        if !self.has_square_labels() {
+            return Err(SolveError::NotSquare.into());
+        }
+
+        let successors = self.successors();
+        let absorbing = absorbing_indices(&successors);
+        if absorbing.is_empty() {
+            return Err(AbsorptionError::NoAbsorbingStates);
+        }
+        if !all_reach(&successors, &absorbing) {
+            return Err(AbsorptionError::NotAbsorbing);
+        }
+
+        let mut is_absorbing = vec![false; n];
+        for &a in &absorbing {
+            is_absorbing[a] = true;
+        }
+        let transient: Vec<usize> = (0..n).filter(|&i| !is_absorbing[i]).collect();
+        let mut position = vec![usize::MAX; n];
+        for (k, &i) in transient.iter().enumerate() {
+            position[i] = k;
+        }
+
+        // I − Q over transient states
+        let m = transient.len();
+        let mut tri = TriMat::new((m, m));
+        for (k, &i) in transient.iter().enumerate() {
+            tri.add_triplet(k, k, 1.0);
+            for &(j, p) in successors[i].iter().filter(|(j, _)| !is_absorbing[*j]) {
+                tri.add_triplet(k, position[j], -p);
+            }
+        }
+        let system: CsMat<f64> = tri.to_csr();
+        let lu = SparseLu::factorize(&system)?;
+
+        let label = |i: usize| self.matrix.x_ix_map.value_of(i).cloned();
+
+        // Columns of N, one solve per transient state
+        let mut fundamental = Vec::new();
+        for (k, &j) in transient.iter().enumerate() {
+            let mut unit = Array1::zeros(m);
+            unit[k] = 1.0;
+            let column = lu.solve(&unit)?;
+            for (l, &value) in column.iter().enumerate() {
+                if value != 0.0 {
+                    fundamental.extend(
+                        label(transient[l])
+                            .zip(label(j))
+                            .map(|(x, y)| (x, y, value)),
+                    );
+                }
+            }
+        }
+
+        // Columns of B = N R, one solve per absorbing state
+        let mut probabilities = Vec::new();
+        for &a in &absorbing {
+            let mut rhs = Array1::zeros(m);
+            for (k, &i) in transient.iter().enumerate() {
+                rhs[k] = successors[i]
+                    .iter()
+                    .filter(|(j, _)| *j == a)
+                    .map(|(_, p)| p)
+                    .sum();
+            }
+            let column = lu.solve(&rhs)?;
+            for (l, &value) in column.iter().enumerate() {
+                if value != 0.0 {
+                    probabilities.extend(
+                        label(transient[l])
+                            .zip(label(a))
+                            .map(|(x, y)| (x, y, value)),
+                    );
+                }
+            }
+            probabilities.extend(label(a).map(|x| (x.clone(), x, 1.0)));
+        }
+
+        // t = N 1 and N t, for the variance
+        let steps = lu.solve(&Array1::ones(m))?;
+        let visits = lu.solve(&steps)?;
+
+        let mut expected_steps = Array1::zeros(n);
+        let mut steps_variance = Array1::zeros(n);
+        for (k, &i) in transient.iter().enumerate() {
+            expected_steps[i] = steps[k];
+            steps_variance[i] = (2.0 * visits[k] - steps[k] - steps[k] * steps[k]).max(0.0);
+        }
+
+        Ok(Absorption {
+            absorbing: absorbing.iter().filter_map(|&i| label(i)).collect(),
+            transient: transient.iter().filter_map(|&i| label(i)).collect(),
+            fundamental: Matrix::from_assoc(fundamental),
+            probabilities: Matrix::from_assoc(probabilities),
+            expected_steps: Vector {
+                values: expected_steps,
+                ix_map: self.matrix.x_ix_map.clone(),
+            },
+            steps_variance: Vector {
+                values: steps_variance,
+                ix_map: self.matrix.x_ix_map.clone(),
+            },
+        })
+    }
+}
+
+fn absorbing_indices(successors: &[Vec<(usize, f64)>]) -> Vec<usize> {
+    successors
+        .iter()
+        .enumerate()
+        .filter(|(i, succ)| !succ.is_empty() && succ.iter().all(|(j, _)| j == i))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+// Whether every state reaches one of `targets`, by backward search.
+fn all_reach(successors: &[Vec<(usize, f64)>], targets: &[usize]) -> bool {
+    let mut predecessors = vec![Vec::new(); successors.len()];
+    for (i, succ) in successors.iter().enumerate() {
+        for &(j, _) in succ {
+            predecessors[j].push(i);
+        }
+    }
+
+    let mut reached = vec![false; successors.len()];
+    let mut queue: VecDeque<usize> = targets.iter().copied().collect();
+    for &t in targets {
+        reached[t] = true;
+    }
+    while let Some(j) = queue.pop_front() {
+        for &i in &predecessors[j] {
+            if !reached[i] {
+                reached[i] = true;
+                queue.push_back(i);
+            }
+        }
+    }
+
+    reached.into_iter().all(|r| r)
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum AbsorptionError {
+    #[error("chain has no absorbing state")]
+    NoAbsorbingStates,
+    #[error("some states cannot reach an absorbing state")]
+    NotAbsorbing,
+    #[error(transparent)]
+    Solve(#[from] SolveError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gamblers_ruin() {
+        // Fair walk on 0..=3, absorbed at both ends
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            (0, 0, 1.0),
+            (1, 0, 0.5),
+            (1, 2, 0.5),
+            (2, 1, 0.5),
+            (2, 3, 0.5),
+            (3, 3, 1.0),
+        ]))
+        .unwrap();
+
+        let absorption = markov.absorption().unwrap();
+        assert_eq!(absorption.absorbing, vec![0, 3]);
+        assert_eq!(absorption.transient, vec![1, 2]);
+
+        // N = [[4/3, 2/3], [2/3, 4/3]]
+        assert!((absorption.fundamental.get(&1, &1).unwrap() - 4.0 / 3.0).abs() < 1e-12);
+        assert!((absorption.fundamental.get(&1, &2).unwrap() - 2.0 / 3.0).abs() < 1e-12);
+
+        // From 1, ruin with probability 2/3
+        assert!((absorption.probabilities.get(&1, &0).unwrap() - 2.0 / 3.0).abs() < 1e-12);
+        assert!((absorption.probabilities.get(&1, &3).unwrap() - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(absorption.probabilities.get(&3, &3), Some(1.0));
+
+        // t = (2, 2) and Var = (2N − I)t − t² = (2, 2)
+        assert!((absorption.expected_steps.get(&1).unwrap() - 2.0).abs() < 1e-12);
+        assert_eq!(absorption.expected_steps.get(&0), Some(0.0));
+        assert!((absorption.steps_variance.get(&2).unwrap() - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_sinks_become_absorbing() {
+        // "end" has incoming edges only, "stuck" has an explicit zero row
+        let matrix = Matrix::from_assoc(vec![
+            ("start", "start", 1.0),
+            ("start", "end", 1.0),
+            ("start", "stuck", 2.0),
+            ("stuck", "start", 0.0),
+        ]);
+        assert!(Markov::from_matrix(matrix.clone()).is_err());
+
+        let markov = Markov::from_matrix_with_sinks(matrix).unwrap();
+        assert_eq!(markov.absorbing_states(), vec!["end", "stuck"]);
+
+        let absorption = markov.absorption().unwrap();
+        assert!(
+            (absorption.probabilities.get(&"start", &"stuck").unwrap() - 2.0 / 3.0).abs() < 1e-12
+        );
+        // Geometric number of steps with success probability 3/4
+        assert!((absorption.expected_steps.get(&"start").unwrap() - 4.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_unreachable_absorption_is_rejected() {
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "a", 1.0),
+            ("b", "c", 1.0),
+            ("c", "b", 1.0),
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            markov.absorption().unwrap_err(),
+            AbsorptionError::NotAbsorbing
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::VecDeque;
+
+use crate::linalg::SolveError;
+use crate::markov::Markov;
+use crate::matrix::Matrix;
+use crate::prob::Prob;
+use crate::vector::Vector;
+
+/// Maximal set of mutually reachable states.
+#[derive(Debug, Clone)]
+pub struct CommunicatingClass<X> {
+    /// States of the class, in label order.
+    pub states: Vec<X>,
+    /// A closed class cannot be left: its states are recurrent.
+    /// States of open classes are transient.
+    pub closed: bool,
+    /// Greatest common divisor of the lengths of the cycles in the class.
+    /// Zero when the class has no internal transition (a state that can
+    /// never be revisited).
+    pub period: usize,
+}
+
+impl<X> CommunicatingClass<X> {
+    pub fn is_recurrent(&self) -> bool {
+        self.closed
+    }
+
+    pub fn is_transient(&self) -> bool {
+        !self.closed
+    }
+
+    pub fn is_periodic(&self) -> bool {
+        self.period > 1
+    }
+}
+
+/// Decomposition of a chain into communicating classes, ordered by the
+/// smallest label of each class.
+#[derive(Debug, Clone)]
+pub struct ClassDecomposition<X> {
+    pub classes: Vec<CommunicatingClass<X>>,
+}
+
+impl<X> ClassDecomposition<X>
+where
+    X: Ord + Clone,
+{
+    /// A single class containing every state.
+    pub fn is_irreducible(&self) -> bool {
+        self.classes.len() == 1
+    }
+
+    /// Irreducible and aperiodic: the stationary distribution is unique and
+    /// every initial distribution converges to it.
+    pub fn is_ergodic(&self) -> bool {
+        self.is_irreducible() && self.classes[0].period == 1
+    }
+
+    pub fn closed_classes(&self) -> impl Iterator<Item = &CommunicatingClass<X>> + '_ {
+        self.classes.iter().filter(|c| c.closed)
+    }
+
+    pub fn transient_classes(&self) -> impl Iterator<Item = &CommunicatingClass<X>> + '_ {
+        self.classes.iter().filter(|c| !c.closed)
+    }
+
+    /// Position of the class containing `x`, if `x` is known.
+    pub fn class_of(&self, x: &X) -> Option<usize> {
+        self.classes
+            .iter()
+            .position(|c| c.states.binary_search(x).is_ok())
+    }
+}
+
+impl<X> Markov<X, X>
+where
+    X: Ord + Clone,
+{
+    /// Decompose the chain into communicating classes, labelling each one
+    /// closed or transient and computing its period.
+    pub fn communicating_classes(&self) -> ClassDecomposition<X> {
+        let successors = self.successors();
+        let leaking = self.leaking_rows();
+        let components = strongly_connected_components(&successors);
+
+        let mut class_id = vec![0; successors.len()];
+        for (c, component) in components.iter().enumerate() {
+            for &i in component {
+                class_id[i] = c;
+            }
+        }
+
+        let classes = components
+            .iter()
+            .enumerate()
+            .map(|(c, component)| {
+                // Weight on a target without a row leaves the chain
+                let closed = component
+                    .iter()
+                    .all(|&i| !leaking[i] && successors[i].iter().all(|&(j, _)| class_id[j] == c));
+                let period = class_period(&successors, &class_id, component);
+                let states = component
+                    .iter()
+                    .filter_map(|&i| self.matrix.x_ix_map.value_of(i).cloned())
+                    .collect();
+
+                CommunicatingClass {
+                    states,
+                    closed,
+                    period,
+                }
+            })
+            .collect();
+
+        ClassDecomposition { classes }
+    }
+
+    /// One stationary distribution per closed class, in the order of
+    /// `ClassDecomposition::closed_classes`. Each distribution is supported
+    /// on its class and defined over all states of the chain.
+    pub fn class_stationary_distributions(
+        &self,
+        tolerance: f64,
+    ) -> Result<Vec<Prob<X>>, SolveError> {
+        let decomposition = self.communicating_classes();
+        let mut distributions = Vec::new();
+
+        for class in decomposition.closed_classes() {
+            let restricted = self
+                .enumerate()
+                .filter(|(x, _, _)| class.states.binary_search(x).is_ok());
+            let markov = Markov::from_matrix(Matrix::from_assoc(restricted))
+                .map_err(|_| SolveError::Singular)?;
+            let stationary = markov.stationary_direct(tolerance)?;
+
+            let vector = Vector::from_assoc(self.matrix.x_ix_map.iter().map(|(_, x)| {
+                let p = stationary.distribution.prob(x).unwrap_or(0.0);
+                (x.clone(), p)
+            }));
+            distributions.push(Prob::from_vector(vector).map_err(|_| SolveError::Singular)?);
+        }
+
+        Ok(distributions)
+    }
+}
+
+/// Strongly connected components (Tarjan), each sorted, ordered by their
+/// smallest index.
+pub(crate) fn strongly_connected_components(successors: &[Vec<(usize, f64)>]) -> Vec<Vec<usize>> {
+    let n = successors.len();
+    let mut index = vec![usize::MAX; n];
+    let mut lowlink = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut counter = 0;
+
+    for root in 0..n {
+        if index[root] != usize::MAX {
+            continue;
+        }
+
+        // Explicit call stack of (node, next successor position)
+        let mut calls = vec![(root, 0)];
+        index[root] = counter;
+        lowlink[root] = counter;
+        counter += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(frame) = calls.last_mut() {
+            let v = frame.0;
+            if frame.1 < successors[v].len() {
+                let w = successors[v][frame.1].0;
+                frame.1 += 1;
+
+                if index[w] == usize::MAX {
+                    index[w] = counter;
+                    lowlink[w] = counter;
+                    counter += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    calls.push((w, 0));
+                } else if on_stack[w] {
+                    lowlink[v] = lowlink[v].min(index[w]);
+                }
+                continue;
+            }
+
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[v]);
+            }
+
+            if lowlink[v] == index[v] {
+                let mut component = Vec::new();
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                components.push(component);
+            }
+        }
+    }
+
+    components.sort_by_key(|c| c[0]);
+    components
+}
+
+// Period of a class: gcd of level[u] + 1 - level[v] over internal edges u -> v,
+// with levels taken from a breadth-first search inside the class.
+fn class_period(
+    successors: &[Vec<(usize, f64)>],
+    class_id: &[usize],
+    component: &[usize],
+) -> usize {
+    let class = class_id[component[0]];
+    let mut level = vec![usize::MAX; successors.len()];
+    let mut queue = VecDeque::from([component[0]]);
+    level[component[0]] = 0;
+
+    let mut period = 0;
+    while let Some(u) = queue.pop_front() {
+        for &(v, _) in successors[u].iter().filter(|(v, _)| class_id[*v] == class) {
+            if level[v] == usize::MAX {
+                level[v] = level[u] + 1;
+                queue.push_back(v);
+            } else {
+                period = gcd(period, (level[u] + 1).abs_diff(level[v]));
+            }
+        }
+    }
+    period
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reducible_chain_classes() {
+        // t leaks into the two-cycle {a, b} and the absorbing state c
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "b", 1.0),
+            ("b", "a", 1.0),
+            ("c", "c", 1.0),
+            ("t", "a", 1.0),
+            ("t", "c", 1.0),
+            ("t", "t", 2.0),
+        ]))
+        .unwrap();
+
+        let decomposition = markov.communicating_classes();
+        assert_eq!(decomposition.classes.len(), 3);
+        assert!(!decomposition.is_irreducible());
+
+        let ab = &decomposition.classes[0];
+        assert_eq!(ab.states, vec!["a", "b"]);
+        assert!(ab.is_recurrent());
+        assert_eq!(ab.period, 2);
+
+        let c = &decomposition.classes[1];
+        assert!(c.closed);
+        assert_eq!(c.period, 1);
+
+        let t = &decomposition.classes[2];
+        assert!(t.is_transient());
+        assert_eq!(decomposition.class_of(&"t"), Some(2));
+
+        let distributions = markov.class_stationary_distributions(1e-10).unwrap();
+        assert_eq!(distributions.len(), 2);
+        assert!((distributions[0].prob(&"a").unwrap() - 0.5).abs() < 1e-12);
+        assert_eq!(distributions[0].prob(&"t"), Some(0.0));
+        assert!((distributions[1].prob(&"c").unwrap() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_leak_into_rowless_sink_opens_class() {
+        // b is a column without a row: mass sent there leaves the chain
+        let markov =
+            Markov::from_matrix(Matrix::from_assoc(vec![("a", "a", 0.5), ("a", "b", 0.5)]))
+                .unwrap();
+
+        let decomposition = markov.communicating_classes();
+        assert_eq!(decomposition.classes.len(), 1);
+        assert!(decomposition.classes[0].is_transient());
+        assert_eq!(decomposition.closed_classes().count(), 0);
+        assert!(markov.absorbing_states().is_empty());
+    }
+
+    #[test]
+    fn test_irreducible_chain_period() {
+        // Cycles of length 2 and 3 give period 1
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            (1, 2, 1.0),
+            (2, 1, 1.0),
+            (2, 3, 1.0),
+            (3, 1, 1.0),
+        ]))
+        .unwrap();
+
+        let decomposition = markov.communicating_classes();
+        assert!(decomposition.is_ergodic());
+        assert_eq!(decomposition.classes[0].states, vec![1, 2, 3]);
+    }
+}
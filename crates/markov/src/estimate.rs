@@ -0,0 +1,379 @@
+use std::collections::BTreeSet;
+
+use crate::markov::{BuildError, Markov};
+use crate::matrix::Matrix;
+use crate::prob::Prob;
+use crate::vector::Vector;
+
+/// Transition counts n_xy gathered from label sequences. Every observed
+/// state is both a row and a column label.
+#[derive(Debug, Clone)]
+pub struct TransitionCounts<X> {
+    pub matrix: Matrix<X, X>,
+}
+
+/// Dirichlet posterior over the rows of a kernel.
+#[derive(Debug, Clone)]
+pub struct Posterior<X> {
+    /// Posterior mean (n_xy + α) / (n_x + mα).
+    pub mean: Markov<X, X>,
+    /// Lower end of the equal-tailed credible interval of each entry.
+    pub lower: Matrix<X, X>,
+    /// Upper end of the equal-tailed credible interval of each entry.
+    pub upper: Matrix<X, X>,
+    pub level: f64,
+}
+
+/// Reversible maximum-likelihood kernel with its stationary distribution.
+#[derive(Debug, Clone)]
+pub struct ReversibleEstimate<X> {
+    pub markov: Markov<X, X>,
+    pub stationary: Prob<X>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+impl<X> TransitionCounts<X>
+where
+    X: Ord + Clone,
+{
+    /// Count the transitions of each sequence; sequences are not joined.
+    pub fn from_sequences<I, S>(sequences: I) -> Result<Self, EstimationError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<[X]>,
+    {
+        let mut states = BTreeSet::new();
+        let mut entries = Vec::new();
+
+        for sequence in sequences {
+            let sequence = sequence.as_ref();
+            states.extend(sequence.iter().cloned());
+            entries.extend(
+                sequence
+                    .windows(2)
+                    .map(|w| (w[0].clone(), w[1].clone(), 1.0)),
+            );
+        }
+
+        if entries.is_empty() {
+            return Err(EstimationError::NoTransitions);
+        }
+
+        // Explicit zeros keep unvisited rows and columns in the label maps
+        entries.extend(states.into_iter().map(|x| (x.clone(), x, 0.0)));
+
+        Ok(Self {
+            matrix: Matrix::from_assoc(entries),
+        })
+    }
+
+    /// Total number of observed transitions.
+    pub fn total(&self) -> f64 {
+        self.matrix.values.data().iter().sum()
+    }
+
+    /// Maximum-likelihood kernel n_xy / n_x. States that were never left
+    /// are made absorbing.
+    pub fn mle(&self) -> Result<Markov<X, X>, EstimationError> {
+        Ok(Markov::from_matrix_with_sinks(self.matrix.clone())?)
+    }
+
+    /// Dirichlet(α) prior on every row over all observed states, with
+    /// equal-tailed credible intervals at `level` for each entry. The
+    /// marginal of an entry is Beta(n_xy + α, n_x + mα − n_xy − α).
+    pub fn posterior(&self, pseudocount: f64, level: f64) -> Result<Posterior<X>, EstimationError> {
+        if !(pseudocount.is_finite() && pseudocount >= 0.0) {
+            return Err(EstimationError::InvalidPseudocount);
+        }
+        if !(level > 0.0 && level < 1.0) {
+            return Err(EstimationError::InvalidLevel);
+        }
+
+        let n = self.matrix.x_ix_map.len();
+        let dense = self.matrix.values.to_dense();
+        let tail = (1.0 - level) / 2.0;
+
+        let mut mean = Vec::new();
+        let mut lower = Vec::new();
+        let mut upper = Vec::new();
+
+        for (i, x) in self.matrix.x_ix_map.iter() {
+            let row_total = dense.row(i).sum() + pseudocount * n as f64;
+            for (j, y) in self.matrix.y_ix_map.iter() {
+                let a = dense[[i, j]] + pseudocount;
+                let b = row_total - a;
+                if row_total > 0.0 {
+                    mean.push((x.clone(), y.clone(), a / row_total));
+                }
+                lower.push((x.clone(), y.clone(), beta_quantile(tail, a, b)));
+                upper.push((x.clone(), y.clone(), beta_quantile(1.0 - tail, a, b)));
+            }
+        }
+
+        let mean = Markov::from_matrix_with_sinks(Matrix::from_assoc(mean))?;
+
+        Ok(Posterior {
+            mean,
+            lower: Matrix::from_assoc(lower),
+            upper: Matrix::from_assoc(upper),
+            level,
+        })
+    }
+
+    /// Maximum-likelihood kernel under detailed balance, by the fixed-point
+    /// iteration x_xy ← (n_xy + n_yx) / (n_x / x_x + n_y / x_y) on the
+    /// symmetric flows x_xy, with P_xy = x_xy / x_x.
+    pub fn reversible_mle(
+        &self,
+        tolerance: f64,
+        max_iterations: usize,
+    ) -> Result<ReversibleEstimate<X>, EstimationError> {
+        let n = self.matrix.x_ix_map.len();
+        let dense = self.matrix.values.to_dense();
+        let row_counts: Vec<f64> = (0..n).map(|i| dense.row(i).sum()).collect();
+
+        // Upper-triangular pairs with symmetric counts c_xy + c_yx > 0
+        let pairs: Vec<(usize, usize, f64)> = (0..n)
+            .flat_map(|i| (i..n).map(move |j| (i, j)))
+            .map(|(i, j)| (i, j, dense[[i, j]] + dense[[j, i]]))
+            .filter(|(_, _, c)| *c > 0.0)
+            .collect();
+
+        let mut flows: Vec<f64> = pairs.iter().map(|(_, _, c)| *c).collect();
+        let mut iterations = 0;
+        let mut converged = false;
+
+        while iterations < max_iterations {
+            iterations += 1;
+            let totals = flow_totals(n, &pairs, &flows);
+
+            let mut change: f64 = 0.0;
+            for (flow, &(i, j, c)) in flows.iter_mut().zip(&pairs) {
+                let denominator = row_counts[i] / totals[i] + row_counts[j] / totals[j];
+                let next = if denominator > 0.0 {
+                    c / denominator
+                } else {
+                    *flow
+                };
+                change = change.max((next - *flow).abs());
+                *flow = next;
+            }
+
+            // Flows are only defined up to scale
+            let sum: f64 = flows.iter().sum();
+            flows.iter_mut().for_each(|f| *f /= sum);
+            if change / sum < tolerance {
+                converged = true;
+                break;
+            }
+        }
+
+        let totals = flow_totals(n, &pairs, &flows);
+        let label = |i: usize| self.matrix.x_ix_map.value_of(i).cloned();
+        let entries = pairs.iter().zip(&flows).flat_map(|(&(i, j, _), &f)| {
+            let forward = (label(i), label(j), f);
+            let backward = (i != j).then(|| (label(j), label(i), f));
+            std::iter::once(forward).chain(backward)
+        });
+        let entries = entries.filter_map(|(x, y, f)| Some((x?, y?, f)));
+
+        let markov = Markov::from_matrix_with_sinks(Matrix::from_assoc(entries))?;
+        let stationary = Prob::from_vector(Vector {
+            values: totals.into(),
+            ix_map: self.matrix.x_ix_map.clone(),
+        })
+        .map_err(|_| EstimationError::NoTransitions)?;
+
+        Ok(ReversibleEstimate {
+            markov,
+            stationary,
+            iterations,
+            converged,
+        })
+    }
+}
+
+// Row sums x_x = Σ_y x_xy of the symmetric flow matrix
+fn flow_totals(n: usize, pairs: &[(usize, usize, f64)], flows: &[f64]) -> Vec<f64> {
+    let mut totals = vec![0.0; n];
+    for (&(i, j, _), &f) in pairs.iter().zip(flows) {
+        totals[i] += f;
+        if i != j {
+            totals[j] += f;
+        }
+    }
+    totals
+}
+
+// Quantile of Beta(a, b) by bisection on the regularized incomplete beta
+// function. Degenerate parameters give point masses.
+fn beta_quantile(q: f64, a: f64, b: f64) -> f64 {
+    if a <= 0.0 {
+        return 0.0;
+    }
+    if b <= 0.0 {
+        return 1.0;
+    }
+
+    let (mut lo, mut hi) = (0.0, 1.0);
+    for _ in 0..100 {
+        let mid = 0.5 * (lo + hi);
+        if incomplete_beta(mid, a, b) < q {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+        if hi - lo < 1e-12 {
+            break;
+        }
+    }
+    0.5 * (lo + hi)
+}
+
+// Regularized incomplete beta I_x(a, b), by its continued fraction
+fn incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    let log_front = ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        log_front.exp() * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - log_front.exp() * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+// Modified Lentz evaluation of the continued fraction for I_x(a, b)
+fn beta_continued_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+
+    for m in 1..300 {
+        let m = m as f64;
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+
+        for coefficient in [even, odd] {
+            d = 1.0 + coefficient * d;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = 1.0 + coefficient / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            h *= d * c;
+        }
+
+        if (d * c - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    h
+}
+
+// Lanczos approximation of ln Γ(x) for x > 0
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        // Reflection formula
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .skip(1)
+        .fold(COEFFICIENTS[0], |acc, (k, c)| acc + c / (x + k as f64));
+
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum EstimationError {
+    #[error("sequences contain no transitions")]
+    NoTransitions,
+    #[error("pseudocount must be finite and non-negative")]
+    InvalidPseudocount,
+    #[error("credible level must lie strictly between 0 and 1")]
+    InvalidLevel,
+    #[error(transparent)]
+    Build(#[from] BuildError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mle_and_posterior_from_sequences() {
+        let counts =
+            TransitionCounts::from_sequences([vec!["a", "b", "a", "a"], vec!["b", "c"]]).unwrap();
+        assert_eq!(counts.total(), 4.0);
+        assert_eq!(counts.matrix.get(&"a", &"b"), Some(1.0));
+
+        let mle = counts.mle().unwrap();
+        assert_eq!(mle.matrix.get(&"a", &"a"), Some(0.5));
+        assert_eq!(mle.matrix.get(&"b", &"c"), Some(0.5));
+        // c was never left
+        assert_eq!(mle.matrix.get(&"c", &"c"), Some(1.0));
+
+        let posterior = counts.posterior(1.0, 0.9).unwrap();
+        // Row a: counts (1, 1, 0) plus one pseudocount each
+        assert!((posterior.mean.matrix.get(&"a", &"c").unwrap() - 0.2).abs() < 1e-12);
+        assert!((posterior.mean.matrix.get(&"c", &"a").unwrap() - 1.0 / 3.0).abs() < 1e-12);
+
+        // Beta(1, 1) is uniform, so the interval is (0.05, 0.95)
+        let single = TransitionCounts::from_sequences([vec![1, 2]]).unwrap();
+        let uniform = single.posterior(1.0, 0.9).unwrap();
+        assert!((uniform.lower.get(&2, &1).unwrap() - 0.05).abs() < 1e-9);
+        assert!((uniform.upper.get(&2, &1).unwrap() - 0.95).abs() < 1e-9);
+
+        assert!(TransitionCounts::<i32>::from_sequences([vec![1]]).is_err());
+    }
+
+    #[test]
+    fn test_reversible_mle_satisfies_detailed_balance() {
+        let counts = TransitionCounts::from_sequences([vec![
+            "a", "b", "c", "a", "b", "a", "c", "c", "b", "b", "a", "b", "c", "a",
+        ]])
+        .unwrap();
+        let estimate = counts.reversible_mle(1e-12, 10_000).unwrap();
+        assert!(estimate.converged);
+
+        let p = &estimate.markov;
+        let pi = &estimate.stationary;
+        for (x, y, p_xy) in p.enumerate() {
+            let p_yx = p.matrix.get(&y, &x).unwrap_or(0.0);
+            let balance = pi.prob(&x).unwrap() * p_xy - pi.prob(&y).unwrap() * p_yx;
+            assert!(balance.abs() < 1e-9, "{x} -> {y}: {balance}");
+        }
+        assert!(p.stationary_residual(pi) < 1e-9);
+    }
+}
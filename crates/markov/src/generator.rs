@@ -0,0 +1,353 @@
+use ndarray::{linalg::Dot, Array1};
+
+use crate::linalg::SolveError;
+use crate::markov::Markov;
+use crate::matrix::Matrix;
+use crate::prob::Prob;
+use crate::stationary::Stationary;
+use crate::vector::Vector;
+
+/// Tolerance on row sums when validating a generator, relative to the
+/// largest rate of the row.
+const ROW_SUM_TOLERANCE: f64 = 1e-9;
+
+/// Largest Λt handled by a single uniformization step. Beyond it the
+/// Poisson weight e^{-Λt} underflows, so the time is split.
+const MAX_STEP_RATE: f64 = 50.0;
+
+/// Infinitesimal generator of a continuous-time chain: non-negative
+/// off-diagonal rates, rows summing to zero.
+#[derive(Debug, Clone)]
+pub struct Generator<X> {
+    pub matrix: Matrix<X, X>,
+}
+
+/// Transient distribution p(t) with the uniformization diagnostics.
+#[derive(Debug, Clone)]
+pub struct Transient<X> {
+    pub distribution: Prob<X>,
+    /// Number of Poisson terms summed, over all time steps.
+    pub terms: usize,
+    /// Upper bound on the total variation distance to the exact p(t).
+    pub error_bound: f64,
+}
+
+impl<X> Generator<X>
+where
+    X: Ord + Clone,
+{
+    /// Validate a full generator matrix, diagonal included.
+    pub fn from_matrix(matrix: Matrix<X, X>) -> Result<Self, GeneratorError> {
+        if matrix.x_ix_map.is_empty() {
+            return Err(GeneratorError::EmptyMatrix);
+        }
+        if matrix.x_ix_map != matrix.y_ix_map {
+            return Err(GeneratorError::NotSquare);
+        }
+
+        let n = matrix.x_ix_map.len();
+        let mut sums = vec![0.0; n];
+        let mut scales = vec![0.0_f64; n];
+        for (&val, (i, j)) in matrix.values.iter() {
+            if i != j && val < 0.0 {
+                return Err(GeneratorError::NegativeRate);
+            }
+            sums[i] += val;
+            scales[i] = scales[i].max(val.abs());
+        }
+
+        if sums
+            .iter()
+            .zip(&scales)
+            .any(|(s, scale)| s.abs() > ROW_SUM_TOLERANCE * scale.max(1.0))
+        {
+            return Err(GeneratorError::NonZeroRowSum);
+        }
+
+        Ok(Self { matrix })
+    }
+
+    /// Build a generator from off-diagonal rates; the diagonal of `rates`
+    /// is ignored and replaced by minus the exit rate. Labels that only
+    /// appear as targets become absorbing states.
+    pub fn from_rates(rates: &Matrix<X, X>) -> Result<Self, GeneratorError> {
+        let mut entries = Vec::new();
+        let mut exit = std::collections::BTreeMap::new();
+
+        for (&val, (i, j)) in rates.values.iter() {
+            let (Some(x), Some(y)) = (rates.x_ix_map.value_of(i), rates.y_ix_map.value_of(j))
+            else {
+                continue;
+            };
+            exit.entry(y.clone()).or_insert(0.0);
+            if x == y {
+                continue;
+            }
+            if val < 0.0 {
+                return Err(GeneratorError::NegativeRate);
+            }
+            *exit.entry(x.clone()).or_insert(0.0) += val;
+            entries.push((x.clone(), y.clone(), val));
+        }
+        for (_, x) in rates.x_ix_map.iter() {
+            exit.entry(x.clone()).or_insert(0.0);
+        }
+        entries.extend(exit.into_iter().map(|(x, q)| (x.clone(), x, -q)));
+
+        Self::from_matrix(Matrix::from_assoc(entries))
+    }
+
+    /// Total rate q_x = −Q_xx of leaving each state.
+    pub fn exit_rates(&self) -> Vector<X> {
+        let mut values = Array1::zeros(self.matrix.x_ix_map.len());
+        for (&val, (i, j)) in self.matrix.values.iter() {
+            if i == j {
+                values[i] = -val;
+            }
+        }
+        Vector {
+            values,
+            ix_map: self.matrix.x_ix_map.clone(),
+        }
+    }
+
+    /// Mean holding time 1/q_x of each state; infinite for absorbing states.
+    pub fn mean_holding_times(&self) -> Vector<X> {
+        let mut times = self.exit_rates();
+        times.mapv_inplace(|q| if q > 0.0 { 1.0 / q } else { f64::INFINITY });
+        times
+    }
+
+    /// Smallest rate valid for `uniformize`: the largest exit rate, or one
+    /// when no state can be left.
+    pub fn uniformization_rate(&self) -> f64 {
+        let max = self.exit_rates().values().fold(0.0_f64, |m, q| m.max(*q));
+        if max > 0.0 {
+            max
+        } else {
+            1.0
+        }
+    }
+
+    /// Uniformized kernel P = I + Q/Λ. The continuous chain is the jump
+    /// process of P at the events of a Poisson clock of rate Λ.
+    pub fn uniformize(&self, rate: f64) -> Result<Markov<X, X>, GeneratorError> {
+        if !(rate.is_finite() && rate > 0.0)
+            || self
+                .exit_rates()
+                .values()
+                .any(|q| *q > rate * (1.0 + ROW_SUM_TOLERANCE))
+        {
+            return Err(GeneratorError::RateTooSmall);
+        }
+
+        let entries = self
+            .enumerate()
+            .map(|(x, y, q)| {
+                let p = if x == y { 1.0 + q / rate } else { q / rate };
+                (x, y, p.max(0.0))
+            })
+            .chain(
+                self.matrix
+                    .x_ix_map
+                    .iter()
+                    .map(|(_, x)| (x.clone(), x.clone(), 0.0)),
+            );
+
+        Ok(Markov::from_matrix(Matrix::from_assoc(entries))?)
+    }
+
+    /// Embedded jump chain P_xy = Q_xy / q_x for x ≠ y. Absorbing states
+    /// keep a self-loop.
+    pub fn embedded_chain(&self) -> Result<Markov<X, X>, GeneratorError> {
+        let exit = self.exit_rates();
+        let entries = self.enumerate().filter_map(|(x, y, q)| {
+            let q_x = exit.get(&x).unwrap_or(0.0);
+            if q_x <= 0.0 {
+                (x == y).then_some((x, y, 1.0))
+            } else {
+                (x != y).then_some((x, y, q))
+            }
+        });
+
+        Ok(Markov::from_matrix(Matrix::from_assoc(entries))?)
+    }
+
+    /// Solve πQ = 0 with Σπ = 1, through the uniformized kernel which has
+    /// the same stationary distributions. The residual is ‖πQ‖∞.
+    pub fn stationary(&self, tolerance: f64) -> Result<Stationary<X>, SolveError> {
+        let kernel = self
+            .uniformize(self.uniformization_rate())
+            .map_err(|_| SolveError::Singular)?;
+        let mut stationary = kernel.stationary_direct(tolerance)?;
+
+        let flow = stationary.distribution.vector.dot(&self.matrix);
+        stationary.residual = flow.values().fold(0.0_f64, |m, v| m.max(v.abs()));
+        stationary.converged = stationary.residual < tolerance;
+        Ok(stationary)
+    }
+
+    /// Transient distribution p(t) = p(0)e^{tQ} by uniformization:
+    /// p(t) = Σ_k Poisson(k; Λt) p(0)Pᵏ. Terms are summed until the
+    /// neglected Poisson mass is below `tolerance`, which bounds the total
+    /// variation error.
+    pub fn transient(
+        &self,
+        initial: &Prob<X>,
+        t: f64,
+        tolerance: f64,
+    ) -> Result<Transient<X>, GeneratorError> {
+        if !(t.is_finite() && t >= 0.0) {
+            return Err(GeneratorError::InvalidTime);
+        }
+        if initial.vector.ix_map != self.matrix.x_ix_map {
+            return Err(GeneratorError::LabelMismatch);
+        }
+
+        let rate = self.uniformization_rate();
+        let kernel = self.uniformize(rate)?;
+
+        let steps = (rate * t / MAX_STEP_RATE).ceil().max(1.0) as usize;
+        let step_tolerance = tolerance.max(f64::EPSILON) / steps as f64;
+        let lambda = rate * t / steps as f64;
+
+        let mut current = initial.vector.values.clone();
+        let mut terms = 0;
+        let mut error_bound = 0.0;
+
+        for _ in 0..steps {
+            let mut term = current;
+            let mut weight = (-lambda).exp();
+            let mut mass = weight;
+            let mut sum = &term * weight;
+            let mut k = 0;
+
+            while 1.0 - mass > step_tolerance {
+                k += 1;
+                term = kernel.matrix.values.transpose_view().dot(&term);
+                weight *= lambda / k as f64;
+                mass += weight;
+                sum.scaled_add(weight, &term);
+
+                // The remaining mass stops decreasing once weights underflow
+                if weight == 0.0 && k as f64 > lambda {
+                    break;
+                }
+            }
+
+            terms += k + 1;
+            error_bound += (1.0 - mass).max(0.0);
+            current = sum;
+        }
+
+        let vector = Vector {
+            values: current,
+            ix_map: self.matrix.x_ix_map.clone(),
+        };
+        let distribution = Prob::from_vector(vector)?;
+
+        Ok(Transient {
+            distribution,
+            terms,
+            error_bound,
+        })
+    }
+
+    /// Enumerate all (row_label, col_label, rate) triplets.
+    pub fn enumerate(&self) -> impl Iterator<Item = (X, X, f64)> + '_ {
+        self.matrix
+            .values
+            .iter()
+            .filter_map(move |(val, (row_idx, col_idx))| {
+                let row_label = self.matrix.x_ix_map.value_of(row_idx)?;
+                let col_label = self.matrix.y_ix_map.value_of(col_idx)?;
+                Some((row_label.clone(), col_label.clone(), *val))
+            })
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GeneratorError {
+    #[error("matrix has zero size")]
+    EmptyMatrix,
+    #[error("generator must have the same row and column labels")]
+    NotSquare,
+    #[error("negative off-diagonal rate encountered")]
+    NegativeRate,
+    #[error("a row does not sum to zero")]
+    NonZeroRowSum,
+    #[error("uniformization rate is below the largest exit rate")]
+    RateTooSmall,
+    #[error("time must be finite and non-negative")]
+    InvalidTime,
+    #[error("distribution labels do not match the generator")]
+    LabelMismatch,
+    #[error(transparent)]
+    Build(#[from] crate::markov::BuildError),
+    #[error("transient distribution is invalid: {0}")]
+    Distribution(#[from] crate::prob::BuildError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_state(a: f64, b: f64) -> Generator<&'static str> {
+        Generator::from_rates(&Matrix::from_assoc(vec![("x", "y", a), ("y", "x", b)])).unwrap()
+    }
+
+    #[test]
+    fn test_two_state_transient_matches_closed_form() {
+        // p_x(t) = b/(a+b) + (1 − b/(a+b)) e^{−(a+b)t} from p(0) = δ_x
+        let (a, b) = (2.0, 1.0);
+        let generator = two_state(a, b);
+        let initial = Prob::from_vector(Vector::from_assoc(vec![("x", 1.0), ("y", 0.0)])).unwrap();
+
+        for t in [0.0, 0.3, 1.0, 40.0] {
+            let transient = generator.transient(&initial, t, 1e-12).unwrap();
+            let exact = b / (a + b) + (1.0 - b / (a + b)) * (-(a + b) * t).exp();
+            let p = transient.distribution.prob(&"x").unwrap();
+            assert!((p - exact).abs() < 1e-10, "t = {t}: {p} vs {exact}");
+            assert!(transient.error_bound <= 1e-12);
+        }
+
+        let stationary = generator.stationary(1e-10).unwrap();
+        assert!(stationary.converged);
+        assert!((stationary.distribution.prob(&"x").unwrap() - 1.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_validation_and_jump_chain() {
+        let not_square = Matrix::from_assoc(vec![("x", "x", -1.0), ("x", "y", 2.0)]);
+        assert!(matches!(
+            Generator::from_matrix(not_square),
+            Err(GeneratorError::NotSquare)
+        ));
+
+        let negative = Matrix::from_assoc(vec![("x", "x", 1.0), ("x", "y", -1.0), ("y", "y", 0.0)]);
+        assert!(matches!(
+            Generator::from_matrix(negative),
+            Err(GeneratorError::NegativeRate)
+        ));
+
+        // z is only a target, so it becomes absorbing
+        let generator = Generator::from_rates(&Matrix::from_assoc(vec![
+            ("x", "y", 3.0),
+            ("x", "z", 1.0),
+            ("y", "x", 2.0),
+        ]))
+        .unwrap();
+        let jump = generator.embedded_chain().unwrap();
+        assert_eq!(jump.matrix.get(&"x", &"y"), Some(0.75));
+        assert_eq!(jump.matrix.get(&"z", &"z"), Some(1.0));
+        assert_eq!(generator.mean_holding_times().get(&"x"), Some(0.25));
+
+        assert!(matches!(
+            generator.uniformize(1.0),
+            Err(GeneratorError::RateTooSmall)
+        ));
+        let uniformized = generator.uniformize(4.0).unwrap();
+        assert_eq!(uniformized.matrix.get(&"x", &"x"), Some(0.0));
+        assert_eq!(uniformized.matrix.get(&"y", &"y"), Some(0.5));
+    }
+}
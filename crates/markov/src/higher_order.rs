@@ -0,0 +1,128 @@
+use crate::information::ObservedWords;
+use crate::markov::{BuildError, Markov};
+use crate::matrix::Matrix;
+use crate::prob::Prob;
+use crate::vector::Vector;
+
+/// Exact order-k kernel P(y_{t+1} | y_{t−k+1}, …, y_t) of an observed
+/// process, indexed by histories listed from oldest to newest.
+#[derive(Debug, Clone)]
+pub struct HigherOrderKernel<Y> {
+    pub order: usize,
+    /// Distribution of the histories of length `order`. Only histories of
+    /// positive probability are listed.
+    pub histories: Prob<Vec<Y>>,
+    pub kernel: Markov<Vec<Y>, Y>,
+}
+
+impl<X> Markov<X, X>
+where
+    X: Ord + Clone,
+{
+    /// Order-`order` transition probabilities of the process obtained by
+    /// pushing the chain through `observable`, with the oldest state of
+    /// each history distributed as `reference`. Order 1 recovers the
+    /// usual macro kernel; order 0 gives the law of the first observation.
+    pub fn observed_kernel<Y>(
+        &self,
+        observable: &Markov<X, Y>,
+        reference: &Prob<X>,
+        order: usize,
+    ) -> Result<HigherOrderKernel<Y>, BuildError>
+    where
+        Y: Ord + Clone,
+    {
+        let y_map = &observable.matrix.y_ix_map;
+        let labels = |word: &[usize]| -> Option<Vec<Y>> {
+            word.iter().map(|&y| y_map.value_of(y).cloned()).collect()
+        };
+
+        let mut words = ObservedWords::new(self, observable, reference);
+        for _ in 0..order {
+            words.extend();
+        }
+
+        // Joint law of (history, next), normalized row by row
+        let transitions: Vec<(Vec<Y>, Y, f64)> = words
+            .probabilities()
+            .filter(|(_, p)| *p > 0.0)
+            .filter_map(|(word, p)| {
+                let (next, history) = word.split_last()?;
+                Some((labels(history)?, y_map.value_of(*next)?.clone(), p))
+            })
+            .collect();
+
+        let histories = Vector::from_assoc(
+            transitions
+                .iter()
+                .map(|(history, _, p)| (history.clone(), *p)),
+        );
+        let kernel = Markov::from_matrix(Matrix::from_assoc(transitions))?;
+        // Building the kernel fails when no history has positive mass, and
+        // otherwise every history weight is positive
+        let histories = Prob::from_vector(histories).expect("positive history weights");
+
+        Ok(HigherOrderKernel {
+            order,
+            histories,
+            kernel,
+        })
+    }
+
+    /// Memory of the observed process at orders 1..=`max_order`: the
+    /// conditional mutual information I(Y_{t+1}; Y_{t−k} | Y_{t−k+1}, …, Y_t)
+    /// at `stationary`, in nats. All vanish when the lumped process is
+    /// Markov, and the order-k value vanishes once the process has memory
+    /// shorter than k.
+    pub fn observed_memory<Y>(
+        &self,
+        observable: &Markov<X, Y>,
+        stationary: &Prob<X>,
+        max_order: usize,
+    ) -> Vec<f64>
+    where
+        Y: Ord + Clone,
+    {
+        self.observed_block_entropies(observable, stationary, max_order + 2)
+            .conditional_mutual_informations()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_is_markov_at_order_two() {
+        // Deterministic 3-cycle a -> b -> c -> a seen through {a, b} -> 0, c -> 1
+        let cycle = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "b", 1.0),
+            ("b", "c", 1.0),
+            ("c", "a", 1.0),
+        ]))
+        .unwrap();
+        let pi = Prob::from_vector(Vector::from_assoc(vec![("a", 1.0), ("b", 1.0), ("c", 1.0)]))
+            .unwrap();
+        let observable = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", 0, 1.0),
+            ("b", 0, 1.0),
+            ("c", 1, 1.0),
+        ]))
+        .unwrap();
+
+        let first = cycle.observed_kernel(&observable, &pi, 1).unwrap();
+        assert!((first.kernel.matrix.get(&vec![0], &0).unwrap() - 0.5).abs() < 1e-12);
+        assert!((first.histories.prob(&vec![0]).unwrap() - 2.0 / 3.0).abs() < 1e-12);
+
+        // Two observations fix the phase
+        let second = cycle.observed_kernel(&observable, &pi, 2).unwrap();
+        assert_eq!(second.kernel.matrix.get(&vec![0, 0], &1), Some(1.0));
+        assert_eq!(second.kernel.matrix.get(&vec![1, 0], &0), Some(1.0));
+        assert!(second.histories.prob(&vec![1, 1]).is_none());
+
+        let memory = cycle.observed_memory(&observable, &pi, 3);
+        assert_eq!(memory.len(), 3);
+        assert!((memory[0] - (2.0 / 3.0) * 2.0_f64.ln()).abs() < 1e-12);
+        assert!(memory[1..].iter().all(|m| m.abs() < 1e-12));
+    }
+}
@@ -0,0 +1,179 @@
+use ndarray::Array1;
+use sprs::{CsMat, TriMat};
+
+use crate::linalg::{SolveError, SparseLu};
+use crate::markov::Markov;
+use crate::matrix::Matrix;
+use crate::prob::Prob;
+use crate::vector::Vector;
+
+/// Tolerance for the stationary distribution used by `hitting_times`.
+const STATIONARY_TOLERANCE: f64 = 1e-10;
+
+/// Hitting-time statistics of an irreducible chain.
+#[derive(Debug, Clone)]
+pub struct HittingTimes<X> {
+    pub stationary: Prob<X>,
+    /// Group inverse (I − P)#, the unique matrix with
+    /// (I − P)(I − P)# = I − 1π and π(I − P)# = 0.
+    pub group_inverse: Matrix<X, X>,
+    /// Expected number of steps to reach the column state from the row
+    /// state. The diagonal holds mean return times.
+    pub mean_first_passage: Matrix<X, X>,
+    /// Mean return times 1/π_i.
+    pub mean_return_times: Vector<X>,
+    /// Kemeny constant Σ_j π_j m_ij (j ≠ i), the same for every start i.
+    pub kemeny_constant: f64,
+}
+
+impl<X> Markov<X, X>
+where
+    X: Ord + Clone,
+{
+    /// Mean first-passage times, return times, Kemeny constant and group
+    /// inverse of I − P. Requires an irreducible chain; periodic chains are
+    /// supported.
+    pub fn hitting_times(&self) -> Result<HittingTimes<X>, HittingError> {
+        let n = self.matrix.x_ix_map.len();
+        if !self.has_square_labels() {
+            return Err(SolveError::NotSquare.into());
+        }
+        if !self.communicating_classes().is_irreducible() {
+            return Err(HittingError::Reducible);
+        }
+
+        let stationary = self.stationary_direct(STATIONARY_TOLERANCE)?.distribution;
+        let pi = &stationary.vector.values;
+
+        // The bordered matrix [[I − P, 1], [π, 0]] is invertible for an
+        // irreducible chain, and its inverse is [[(I − P)#, 1], [π, 0]].
+        let mut tri = TriMat::new((n + 1, n + 1));
+        for i in 0..n {
+            tri.add_triplet(i, i, 1.0);
+            tri.add_triplet(i, n, 1.0);
+            tri.add_triplet(n, i, pi[i]);
+        }
+        for (i, succ) in self.successors().into_iter().enumerate() {
+            for (j, p) in succ {
+                tri.add_triplet(i, j, -p);
+            }
+        }
+        let bordered: CsMat<f64> = tri.to_csr();
+        let lu = SparseLu::factorize(&bordered)?;
+
+        let mut group = vec![Array1::zeros(0); n];
+        for (j, column) in group.iter_mut().enumerate() {
+            let mut unit = Array1::zeros(n + 1);
+            unit[j] = 1.0;
+            *column = lu.solve(&unit)?.slice_move(ndarray::s![..n]);
+        }
+
+        let label = |i: usize| self.matrix.x_ix_map.value_of(i).cloned();
+
+        let mut group_inverse = Vec::with_capacity(n * n);
+        let mut mean_first_passage = Vec::with_capacity(n * n);
+        for (j, column) in group.iter().enumerate() {
+            for i in 0..n {
+                let (Some(x), Some(y)) = (label(i), label(j)) else {
+                    continue;
+                };
+                // m_ij = (a#_jj − a#_ij) / π_j, and m_jj = 1 / π_j
+                let passage = if i == j {
+                    1.0 / pi[j]
+                } else {
+                    (column[j] - column[i]) / pi[j]
+                };
+                group_inverse.push((x.clone(), y.clone(), column[i]));
+                mean_first_passage.push((x, y, passage));
+            }
+        }
+
+        let kemeny_constant = (0..n).map(|j| group[j][j]).sum();
+
+        Ok(HittingTimes {
+            mean_return_times: Vector {
+                values: pi.mapv(|p| 1.0 / p),
+                ix_map: self.matrix.x_ix_map.clone(),
+            },
+            stationary,
+            group_inverse: Matrix::from_assoc(group_inverse),
+            mean_first_passage: Matrix::from_assoc(mean_first_passage),
+            kemeny_constant,
+        })
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum HittingError {
+    #[error("chain is not irreducible")]
+    Reducible,
+    #[error(transparent)]
+    Solve(#[from] SolveError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_state_chain() {
+        // P = [[1 − a, a], [b, 1 − b]]: m_01 = 1/a, m_10 = 1/b, K = 1/(a + b)
+        let (a, b) = (0.2, 0.6);
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            (0, 0, 1.0 - a),
+            (0, 1, a),
+            (1, 0, b),
+            (1, 1, 1.0 - b),
+        ]))
+        .unwrap();
+
+        let hitting = markov.hitting_times().unwrap();
+
+        assert!((hitting.mean_first_passage.get(&0, &1).unwrap() - 1.0 / a).abs() < 1e-10);
+        assert!((hitting.mean_first_passage.get(&1, &0).unwrap() - 1.0 / b).abs() < 1e-10);
+        // π = (b, a) / (a + b)
+        assert!((hitting.mean_return_times.get(&0).unwrap() - (a + b) / b).abs() < 1e-10);
+        assert!((hitting.mean_first_passage.get(&1, &1).unwrap() - (a + b) / a).abs() < 1e-10);
+        assert!((hitting.kemeny_constant - 1.0 / (a + b)).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_kemeny_constant_is_independent_of_start() {
+        // Periodic three-cycle with a chord
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "b", 1.0),
+            ("b", "c", 1.0),
+            ("b", "a", 1.0),
+            ("c", "a", 1.0),
+        ]))
+        .unwrap();
+
+        let hitting = markov.hitting_times().unwrap();
+        let pi = &hitting.stationary;
+
+        for start in ["a", "b", "c"] {
+            let k: f64 = ["a", "b", "c"]
+                .iter()
+                .filter(|&&j| j != start)
+                .map(|j| pi.prob(j).unwrap() * hitting.mean_first_passage.get(&start, j).unwrap())
+                .sum();
+            assert!((k - hitting.kemeny_constant).abs() < 1e-10);
+        }
+
+        // Rows of the group inverse sum to zero
+        let sums = hitting.group_inverse.get_rows_sums();
+        assert!(sums.values.iter().all(|s| s.abs() < 1e-10));
+    }
+
+    #[test]
+    fn test_reducible_chain_is_rejected() {
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "a", 1.0),
+            ("b", "a", 1.0),
+            ("b", "b", 1.0),
+        ]))
+        .unwrap();
+
+        assert_eq!(markov.hitting_times().unwrap_err(), HittingError::Reducible);
+    }
+}
@@ -0,0 +1,534 @@
+use crate::markov::{BuildError, Markov};
+use crate::matrix::Matrix;
+use crate::prob::Prob;
+use crate::vector::Vector;
+
+/// Hidden Markov model: a chain on X seen only through an emission kernel
+/// from X to the observed labels Y.
+#[derive(Debug, Clone)]
+pub struct Hmm<X, Y> {
+    pub transition: Markov<X, X>,
+    pub emission: Markov<X, Y>,
+    /// Law of the first hidden state.
+    pub initial: Prob<X>,
+}
+
+/// Filtered laws P(X_t | y_1..y_t) of the hidden states.
+#[derive(Debug, Clone)]
+pub struct Filtering<X> {
+    pub filtered: Vec<Prob<X>>,
+    /// ln P(y_1..y_T).
+    pub log_likelihood: f64,
+}
+
+/// Smoothed laws P(X_t | y_1..y_T) of the hidden states.
+#[derive(Debug, Clone)]
+pub struct Smoothing<X> {
+    pub smoothed: Vec<Prob<X>>,
+    /// ln P(y_1..y_T).
+    pub log_likelihood: f64,
+}
+
+/// Most likely hidden path given the observations.
+#[derive(Debug, Clone)]
+pub struct ViterbiPath<X> {
+    pub states: Vec<X>,
+    /// ln P(x_1..x_T, y_1..y_T) of the path.
+    pub log_probability: f64,
+}
+
+/// Result of Baum-Welch re-estimation.
+#[derive(Debug, Clone)]
+pub struct BaumWelch<X, Y> {
+    pub hmm: Hmm<X, Y>,
+    /// Total log-likelihood of the sequences under `hmm`.
+    pub log_likelihood: f64,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+// Scaled forward-backward pass over one sequence
+struct Pass {
+    symbols: Vec<usize>,
+    alpha: Vec<Vec<f64>>,
+    beta: Vec<Vec<f64>>,
+    scales: Vec<f64>,
+}
+
+impl<X, Y> Hmm<X, Y>
+where
+    X: Ord + Clone,
+    Y: Ord + Clone,
+{
+    /// Pair a hidden chain with its emissions. Every target of the chain
+    /// must be one of its states.
+    pub fn new(
+        transition: Markov<X, X>,
+        emission: Markov<X, Y>,
+        initial: Prob<X>,
+    ) -> Result<Self, HmmError> {
+        let x_map = &transition.matrix.x_ix_map;
+        let closed = transition
+            .matrix
+            .y_ix_map
+            .iter()
+            .all(|(_, x)| x_map.index_of(x).is_some());
+        if !closed {
+            return Err(HmmError::LabelMismatch);
+        }
+
+        Ok(Self {
+            transition,
+            emission,
+            initial,
+        })
+    }
+
+    /// Forward filtering with per-step normalization.
+    pub fn filter(&self, observations: &[Y]) -> Result<Filtering<X>, HmmError> {
+        let symbols = self.symbols(observations)?;
+        let (alpha, scales) = self.forward(&symbols)?;
+        Ok(Filtering {
+            filtered: alpha
+                .into_iter()
+                .map(|a| self.prob(a))
+                .collect::<Result<_, _>>()?,
+            log_likelihood: scales.iter().map(|c| c.ln()).sum(),
+        })
+    }
+
+    /// Forward-backward smoothing.
+    pub fn smooth(&self, observations: &[Y]) -> Result<Smoothing<X>, HmmError> {
+        let pass = self.pass(observations)?;
+        let log_likelihood = pass.scales.iter().map(|c| c.ln()).sum();
+        Ok(Smoothing {
+            smoothed: pass
+                .occupations()
+                .into_iter()
+                .map(|g| self.prob(g))
+                .collect::<Result<_, _>>()?,
+            log_likelihood,
+        })
+    }
+
+    /// ln P(y_1..y_T); negative infinity if the sequence is impossible.
+    pub fn log_likelihood(&self, observations: &[Y]) -> Result<f64, HmmError> {
+        match self.filter(observations) {
+            Ok(filtering) => Ok(filtering.log_likelihood),
+            Err(HmmError::ImpossibleSequence) => Ok(f64::NEG_INFINITY),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Viterbi decoding in log space.
+    pub fn viterbi(&self, observations: &[Y]) -> Result<ViterbiPath<X>, HmmError> {
+        let symbols = self.symbols(observations)?;
+        let emission = self.dense_emission();
+        let successors = self.transition.successors();
+        let n = successors.len();
+        let log_emission = |i: usize, y: usize| emission[i][y].ln();
+
+        let mut delta: Vec<f64> = self
+            .initial_weights()
+            .iter()
+            .enumerate()
+            .map(|(i, p)| p.ln() + log_emission(i, symbols[0]))
+            .collect();
+        let mut back: Vec<Vec<usize>> = Vec::with_capacity(symbols.len());
+
+        for &y in &symbols[1..] {
+            let mut next = vec![f64::NEG_INFINITY; n];
+            let mut from = vec![0; n];
+            for (i, succ) in successors.iter().enumerate() {
+                if delta[i] == f64::NEG_INFINITY {
+                    continue;
+                }
+                for &(j, p) in succ {
+                    let candidate = delta[i] + p.ln();
+                    if candidate > next[j] {
+                        next[j] = candidate;
+                        from[j] = i;
+                    }
+                }
+            }
+            for (j, d) in next.iter_mut().enumerate() {
+                *d += log_emission(j, y);
+            }
+            back.push(from);
+            delta = next;
+        }
+
+        let (mut state, log_probability) = delta
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .ok_or(HmmError::EmptySequence)?;
+        if log_probability == f64::NEG_INFINITY {
+            return Err(HmmError::ImpossibleSequence);
+        }
+
+        let mut path = vec![state];
+        for from in back.iter().rev() {
+            state = from[state];
+            path.push(state);
+        }
+        path.reverse();
+
+        let x_map = &self.transition.matrix.x_ix_map;
+        Ok(ViterbiPath {
+            states: path
+                .into_iter()
+                .filter_map(|i| x_map.value_of(i).cloned())
+                .collect(),
+            log_probability,
+        })
+    }
+
+    /// Baum-Welch (EM) re-estimation of the initial law and of both
+    /// kernels from several observed sequences. Transitions and emissions
+    /// absent from the model stay absent; rows of states that are never
+    /// visited are kept as they are. Stops when the log-likelihood gains
+    /// less than `tolerance`.
+    pub fn baum_welch(
+        &self,
+        sequences: &[Vec<Y>],
+        tolerance: f64,
+        max_iterations: usize,
+    ) -> Result<BaumWelch<X, Y>, HmmError> {
+        let mut hmm = self.clone();
+        let mut log_likelihood = hmm.total_log_likelihood(sequences)?;
+        let mut iterations = 0;
+        let mut converged = false;
+
+        while iterations < max_iterations {
+            hmm = hmm.reestimate(sequences)?;
+            iterations += 1;
+
+            let next = hmm.total_log_likelihood(sequences)?;
+            let gain = next - log_likelihood;
+            log_likelihood = next;
+            if gain.abs() < tolerance {
+                converged = true;
+                break;
+            }
+        }
+
+        Ok(BaumWelch {
+            hmm,
+            log_likelihood,
+            iterations,
+            converged,
+        })
+    }
+
+    fn total_log_likelihood(&self, sequences: &[Vec<Y>]) -> Result<f64, HmmError> {
+        sequences.iter().map(|s| self.log_likelihood(s)).sum()
+    }
+
+    // One EM step: expected initial occupations, transitions and emissions
+    fn reestimate(&self, sequences: &[Vec<Y>]) -> Result<Self, HmmError> {
+        let successors = self.transition.successors();
+        let emission = self.dense_emission();
+        let n = successors.len();
+        let m = self.emission.matrix.y_ix_map.len();
+
+        let mut initial = vec![0.0; n];
+        let mut transitions: Vec<Vec<f64>> =
+            successors.iter().map(|s| vec![0.0; s.len()]).collect();
+        let mut emissions = vec![vec![0.0; m]; n];
+
+        for sequence in sequences {
+            let pass = self.pass(sequence)?;
+            let gamma = pass.occupations();
+
+            initial.iter_mut().zip(&gamma[0]).for_each(|(a, g)| *a += g);
+            for (t, g) in gamma.iter().enumerate() {
+                for (i, &w) in g.iter().enumerate() {
+                    emissions[i][pass.symbols[t]] += w;
+                }
+            }
+            for t in 0..pass.symbols.len() - 1 {
+                let y = pass.symbols[t + 1];
+                let c = pass.scales[t + 1];
+                for (i, succ) in successors.iter().enumerate() {
+                    let a = pass.alpha[t][i];
+                    if a == 0.0 {
+                        continue;
+                    }
+                    for (k, &(j, p)) in succ.iter().enumerate() {
+                        transitions[i][k] += a * p * emission[j][y] * pass.beta[t + 1][j] / c;
+                    }
+                }
+            }
+        }
+
+        let x_map = &self.transition.matrix.x_ix_map;
+        let y_map = &self.emission.matrix.y_ix_map;
+        let label = |i: usize| x_map.value_of(i).cloned();
+
+        // Unvisited rows keep their current probabilities
+        let transition_entries = successors.iter().enumerate().flat_map(|(i, succ)| {
+            let counts = &transitions[i];
+            let visited = counts.iter().sum::<f64>() > 0.0;
+            succ.iter()
+                .zip(counts)
+                .map(move |(&(j, p), &count)| (i, j, if visited { count } else { p }))
+        });
+        let transition = Markov::from_matrix(Matrix::from_assoc(
+            transition_entries
+                .filter(|(_, _, v)| *v > 0.0)
+                .filter_map(|(i, j, v)| Some((label(i)?, label(j)?, v))),
+        ))?;
+
+        let emission_entries = emissions.iter().enumerate().flat_map(|(i, counts)| {
+            let visited = counts.iter().sum::<f64>() > 0.0;
+            let current = &emission[i];
+            (0..m).map(move |y| (i, y, if visited { counts[y] } else { current[y] }))
+        });
+        let emission = Markov::from_matrix(Matrix::from_assoc(
+            emission_entries
+                .filter(|(_, _, v)| *v > 0.0)
+                .filter_map(|(i, y, v)| Some((label(i)?, y_map.value_of(y)?.clone(), v))),
+        ))?;
+
+        let initial = self.prob(initial)?;
+        Hmm::new(transition, emission, initial)
+    }
+
+    fn pass(&self, observations: &[Y]) -> Result<Pass, HmmError> {
+        let symbols = self.symbols(observations)?;
+        let (alpha, scales) = self.forward(&symbols)?;
+        let beta = self.backward(&symbols, &scales);
+        Ok(Pass {
+            symbols,
+            alpha,
+            beta,
+            scales,
+        })
+    }
+
+    // Normalized forward vectors and the normalizing constants
+    fn forward(&self, symbols: &[usize]) -> Result<(Vec<Vec<f64>>, Vec<f64>), HmmError> {
+        let emission = self.dense_emission();
+        let successors = self.transition.successors();
+        let n = successors.len();
+
+        let mut alpha = Vec::with_capacity(symbols.len());
+        let mut scales = Vec::with_capacity(symbols.len());
+        let mut current = self.initial_weights();
+
+        for (t, &y) in symbols.iter().enumerate() {
+            if t > 0 {
+                let mut next = vec![0.0; n];
+                for (i, succ) in successors.iter().enumerate() {
+                    for &(j, p) in succ {
+                        next[j] += current[i] * p;
+                    }
+                }
+                current = next;
+            }
+            for (i, a) in current.iter_mut().enumerate() {
+                *a *= emission[i][y];
+            }
+
+            let scale: f64 = current.iter().sum();
+            if scale <= 0.0 {
+                return Err(HmmError::ImpossibleSequence);
+            }
+            current.iter_mut().for_each(|a| *a /= scale);
+            alpha.push(current.clone());
+            scales.push(scale);
+        }
+
+        Ok((alpha, scales))
+    }
+
+    // Backward vectors scaled by the forward constants
+    fn backward(&self, symbols: &[usize], scales: &[f64]) -> Vec<Vec<f64>> {
+        let emission = self.dense_emission();
+        let successors = self.transition.successors();
+        let n = successors.len();
+
+        let mut beta = vec![vec![1.0; n]; symbols.len()];
+        for t in (0..symbols.len() - 1).rev() {
+            let y = symbols[t + 1];
+            for (i, succ) in successors.iter().enumerate() {
+                beta[t][i] = succ
+                    .iter()
+                    .map(|&(j, p)| p * emission[j][y] * beta[t + 1][j])
+                    .sum::<f64>()
+                    / scales[t + 1];
+            }
+        }
+        beta
+    }
+
+    // Observed labels as emission columns
+    fn symbols(&self, observations: &[Y]) -> Result<Vec<usize>, HmmError> {
+        if observations.is_empty() {
+            return Err(HmmError::EmptySequence);
+        }
+        observations
+            .iter()
+            .map(|y| {
+                self.emission
+                    .matrix
+                    .y_ix_map
+                    .index_of(y)
+                    .ok_or(HmmError::UnknownSymbol)
+            })
+            .collect()
+    }
+
+    // Emission probabilities with rows aligned to the hidden states
+    fn dense_emission(&self) -> Vec<Vec<f64>> {
+        let x_map = &self.transition.matrix.x_ix_map;
+        let mut dense = vec![vec![0.0; self.emission.matrix.y_ix_map.len()]; x_map.len()];
+        for (&val, (i, y)) in self.emission.matrix.values.iter() {
+            let row = self
+                .emission
+                .matrix
+                .x_ix_map
+                .value_of(i)
+                .and_then(|x| x_map.index_of(x));
+            if let Some(row) = row {
+                dense[row][y] = val;
+            }
+        }
+        dense
+    }
+
+    fn initial_weights(&self) -> Vec<f64> {
+        self.transition
+            .matrix
+            .x_ix_map
+            .iter()
+            .map(|(_, x)| self.initial.prob(x).unwrap_or(0.0))
+            .collect()
+    }
+
+    fn prob(&self, weights: Vec<f64>) -> Result<Prob<X>, HmmError> {
+        Prob::from_vector(Vector {
+            values: weights.into(),
+            ix_map: self.transition.matrix.x_ix_map.clone(),
+        })
+        .map_err(|_| HmmError::ImpossibleSequence)
+    }
+}
+
+impl Pass {
+    // γ_t(x) = P(X_t = x | y_1..y_T)
+    fn occupations(&self) -> Vec<Vec<f64>> {
+        self.alpha
+            .iter()
+            .zip(&self.beta)
+            .map(|(a, b)| {
+                let gamma: Vec<f64> = a.iter().zip(b).map(|(a, b)| a * b).collect();
+                let total: f64 = gamma.iter().sum();
+                gamma.into_iter().map(|g| g / total).collect()
+            })
+            .collect()
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum HmmError {
+    #[error("observation sequence is empty")]
+    EmptySequence,
+    #[error("observation is not a label of the emission kernel")]
+    UnknownSymbol,
+    #[error("observation sequence has zero probability")]
+    ImpossibleSequence,
+    #[error("transition targets must be hidden states")]
+    LabelMismatch,
+    #[error(transparent)]
+    Build(#[from] BuildError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Sticky two-state chain with noisy emissions
+    fn weather() -> Hmm<&'static str, char> {
+        let transition = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("rain", "rain", 0.7),
+            ("rain", "sun", 0.3),
+            ("sun", "rain", 0.4),
+            ("sun", "sun", 0.6),
+        ]))
+        .unwrap();
+        let emission = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("rain", 'u', 0.9),
+            ("rain", 'n', 0.1),
+            ("sun", 'u', 0.2),
+            ("sun", 'n', 0.8),
+        ]))
+        .unwrap();
+        let initial =
+            Prob::from_vector(Vector::from_assoc(vec![("rain", 0.6), ("sun", 0.4)])).unwrap();
+        Hmm::new(transition, emission, initial).unwrap()
+    }
+
+    #[test]
+    fn test_forward_backward_matches_enumeration() {
+        let hmm = weather();
+        let observations = ['u', 'u', 'n'];
+
+        // Brute force over all 8 hidden paths
+        let states = ["rain", "sun"];
+        let mut total = 0.0;
+        let mut first_rain = 0.0;
+        let mut best = (0.0, Vec::new());
+        for code in 0..8 {
+            let path: Vec<&str> = (0..3).map(|t| states[(code >> t) & 1]).collect();
+            let mut p = hmm.initial.prob(&path[0]).unwrap();
+            for t in 0..3 {
+                if t > 0 {
+                    p *= hmm.transition.matrix.get(&path[t - 1], &path[t]).unwrap();
+                }
+                p *= hmm.emission.matrix.get(&path[t], &observations[t]).unwrap();
+            }
+            total += p;
+            if path[0] == "rain" {
+                first_rain += p;
+            }
+            if p > best.0 {
+                best = (p, path);
+            }
+        }
+
+        let smoothing = hmm.smooth(&observations).unwrap();
+        assert!((smoothing.log_likelihood - total.ln()).abs() < 1e-12);
+        assert!((smoothing.smoothed[0].prob(&"rain").unwrap() - first_rain / total).abs() < 1e-12);
+
+        let viterbi = hmm.viterbi(&observations).unwrap();
+        assert_eq!(viterbi.states, best.1);
+        assert!((viterbi.log_probability - best.0.ln()).abs() < 1e-12);
+
+        assert!(matches!(hmm.filter(&['x']), Err(HmmError::UnknownSymbol)));
+    }
+
+    #[test]
+    fn test_baum_welch_increases_likelihood() {
+        let hmm = weather();
+        let sequences = vec![
+            "uuunnnuunn".chars().collect::<Vec<_>>(),
+            "nnnnuuuuun".chars().collect::<Vec<_>>(),
+        ];
+        let before: f64 = sequences
+            .iter()
+            .map(|s| hmm.log_likelihood(s).unwrap())
+            .sum();
+
+        let fit = hmm.baum_welch(&sequences, 1e-10, 500).unwrap();
+        assert!(fit.converged);
+        assert!(fit.log_likelihood > before);
+        let row: f64 = ['u', 'n']
+            .iter()
+            .filter_map(|y| fit.hmm.emission.matrix.get(&"sun", y))
+            .sum();
+        assert!((row - 1.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,426 @@
+use ndarray::linalg::Dot;
+use ndarray::Array1;
+
+use crate::markov::{BuildError, Markov};
+use crate::prob::Prob;
+use crate::vector::Vector;
+
+/// Entropies H(X_1..X_k) of the blocks of length k = 1..n of a stationary
+/// process, in nats.
+#[derive(Debug, Clone)]
+pub struct BlockEntropies {
+    pub entropies: Vec<f64>,
+}
+
+impl BlockEntropies {
+    /// Conditional entropies h_k = H_k − H_{k−1}, with h_1 = H_1. They
+    /// decrease to the entropy rate.
+    pub fn entropy_rate_estimates(&self) -> Vec<f64> {
+        let mut previous = 0.0;
+        self.entropies
+            .iter()
+            .map(|&h| {
+                let conditional = h - previous;
+                previous = h;
+                conditional
+            })
+            .collect()
+    }
+
+    /// Entropy rate estimate h_n from the longest block.
+    pub fn entropy_rate(&self) -> Option<f64> {
+        self.entropy_rate_estimates().last().copied()
+    }
+
+    /// I(X_t; X_{t+1}) = 2H_1 − H_2.
+    pub fn mutual_information(&self) -> Option<f64> {
+        match self.entropies[..] {
+            [h1, h2, ..] => Some(2.0 * h1 - h2),
+            _ => None,
+        }
+    }
+
+    /// Memory of the process at orders k = 1..n − 2: the conditional
+    /// mutual information I(Y_{t+1}; Y_{t−k} | Y_{t−k+1}, …, Y_t) =
+    /// h_{k+1} − h_{k+2}. It vanishes from the Markov order on.
+    pub fn conditional_mutual_informations(&self) -> Vec<f64> {
+        self.entropy_rate_estimates()
+            .windows(2)
+            .skip(1)
+            .map(|w| w[0] - w[1])
+            .collect()
+    }
+
+    /// Excess entropy estimate E_n = H_n − n h_n. For a stationary process
+    /// this is the predictive information I(past; future), and the estimate
+    /// is exact once n exceeds the Markov order.
+    pub fn excess_entropy(&self) -> Option<f64> {
+        let n = self.entropies.len();
+        let h = self.entropy_rate()?;
+        Some(self.entropies[n - 1] - n as f64 * h)
+    }
+}
+
+/// Effective information of a kernel in the sense of Hoel, in nats: the
+/// mutual information I(X_t; X_{t+1}) when X_t is set by a uniform
+/// intervention rather than drawn from the dynamics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EffectiveInformation {
+    /// EI = determinism − degeneracy.
+    pub effective_information: f64,
+    /// ln n − ⟨H(P_x)⟩: how reliably a state fixes its successor.
+    pub determinism: f64,
+    /// ln n − H(uP): how much different states lead to the same successors.
+    pub degeneracy: f64,
+    /// EI / ln n, comparable across chains with different numbers of
+    /// states. Zero for a single state.
+    pub effectiveness: f64,
+}
+
+/// Observed words of a fixed length together with their forward vectors
+/// α_w(x) = P(Y_1..Y_k = w, X_k = x). Words of zero probability are
+/// dropped, so the count grows like |Y|ᵏ at most.
+pub(crate) struct ObservedWords {
+    emissions: Vec<Vec<(usize, f64)>>,
+    successors: Vec<Vec<(usize, f64)>>,
+    n_observed: usize,
+    /// Column indices of the observable, with the forward vector.
+    pub words: Vec<(Vec<usize>, Vec<f64>)>,
+}
+
+impl ObservedWords {
+    /// Words of length one, with X_1 distributed as `initial`.
+    pub fn new<X, Y>(markov: &Markov<X, X>, observable: &Markov<X, Y>, initial: &Prob<X>) -> Self
+    where
+        X: Ord + Clone,
+        Y: Ord + Clone,
+    {
+        let x_map = &markov.matrix.x_ix_map;
+
+        // Emission row O(x, ·) for each micro state
+        let mut emissions = vec![Vec::new(); x_map.len()];
+        for (&val, (i, j)) in observable.matrix.values.iter() {
+            let row = observable
+                .matrix
+                .x_ix_map
+                .value_of(i)
+                .and_then(|x| x_map.index_of(x));
+            if let Some(row) = row {
+                emissions[row].push((j, val));
+            }
+        }
+
+        let mut words = Self {
+            emissions,
+            successors: markov.successors(),
+            n_observed: observable.matrix.y_ix_map.len(),
+            words: Vec::new(),
+        };
+        let weights: Vec<f64> = x_map
+            .iter()
+            .map(|(_, x)| initial.prob(x).unwrap_or(0.0))
+            .collect();
+        words.words = words.emit(&[], &weights);
+        words
+    }
+
+    /// Lengthen every word by one step.
+    pub fn extend(&mut self) {
+        let n_states = self.emissions.len();
+        self.words = self
+            .words
+            .iter()
+            .flat_map(|(word, alpha)| {
+                let mut next = vec![0.0; n_states];
+                for (x, &w) in alpha.iter().enumerate().filter(|(_, w)| **w > 0.0) {
+                    for &(target, p) in &self.successors[x] {
+                        next[target] += w * p;
+                    }
+                }
+                self.emit(word, &next)
+            })
+            .collect();
+    }
+
+    /// Upper bound on the number of words after the next `extend`.
+    pub fn extended_len_bound(&self) -> usize {
+        self.words.len().saturating_mul(self.n_observed)
+    }
+
+    pub fn probabilities(&self) -> impl Iterator<Item = (&[usize], f64)> + '_ {
+        self.words
+            .iter()
+            .map(|(word, alpha)| (word.as_slice(), alpha.iter().sum()))
+    }
+
+    // Extend `prefix` by each observed symbol emitted from `weights`
+    fn emit(&self, prefix: &[usize], weights: &[f64]) -> Vec<(Vec<usize>, Vec<f64>)> {
+        let mut alphas = vec![vec![0.0; weights.len()]; self.n_observed];
+        for (x, &w) in weights.iter().enumerate().filter(|(_, w)| **w > 0.0) {
+            for &(y, o) in &self.emissions[x] {
+                alphas[y][x] += w * o;
+            }
+        }
+        alphas
+            .into_iter()
+            .enumerate()
+            .filter(|(_, alpha)| alpha.iter().any(|p| *p > 0.0))
+            .map(|(y, alpha)| {
+                let mut word = prefix.to_vec();
+                word.push(y);
+                (word, alpha)
+            })
+            .collect()
+    }
+}
+
+impl<X> Markov<X, X>
+where
+    X: Ord + Clone,
+{
+    /// Mutual information I(X_t; X_{t+1}) = H(πP) − h between consecutive
+    /// states, with X_t distributed as `stationary`.
+    pub fn mutual_information(&self, stationary: &Prob<X>) -> f64 {
+        stationary.dot(self).entropy() - self.entropy_rate(stationary)
+    }
+
+    /// Effective information under the uniform intervention over the
+    /// states, with its determinism and degeneracy terms. Comparing it
+    /// between a micro chain and a coarse-grained one measures causal
+    /// emergence. Fails with `BuildError::LabelMismatch` unless rows and
+    /// columns carry the same labels, since the intervention must cover
+    /// every state the chain can reach.
+    pub fn effective_information(&self) -> Result<EffectiveInformation, BuildError> {
+        if !self.has_square_labels() {
+            return Err(BuildError::LabelMismatch);
+        }
+        let n = self.matrix.x_ix_map.len();
+        let uniform = Prob {
+            vector: Vector {
+                values: Array1::from_elem(n, 1.0 / n as f64),
+                ix_map: self.matrix.x_ix_map.clone(),
+            },
+        };
+
+        let max_entropy = (n as f64).ln();
+        let determinism = max_entropy - self.entropy_rate(&uniform);
+        let degeneracy = max_entropy - uniform.dot(self).entropy();
+        let effective_information = determinism - degeneracy;
+
+        Ok(EffectiveInformation {
+            effective_information,
+            determinism,
+            degeneracy,
+            effectiveness: if n > 1 {
+                effective_information / max_entropy
+            } else {
+                0.0
+            },
+        })
+    }
+
+    /// Block entropies of the chain started from `stationary`. A Markov
+    /// chain has H_k = H(π) + (k − 1)h, so its excess entropy equals the
+    /// one-step mutual information.
+    pub fn block_entropies(&self, stationary: &Prob<X>, n: usize) -> BlockEntropies {
+        let h1 = stationary.entropy();
+        let h = self.entropy_rate(stationary);
+        BlockEntropies {
+            entropies: (0..n).map(|k| h1 + k as f64 * h).collect(),
+        }
+    }
+
+    /// Block entropies of the process Y_t obtained by pushing each state
+    /// of the chain through `observable`, which is generally not Markov.
+    /// Enumerates every word of positive probability, so the cost grows
+    /// like |Y|ⁿ.
+    pub fn observed_block_entropies<Y>(
+        &self,
+        observable: &Markov<X, Y>,
+        stationary: &Prob<X>,
+        n: usize,
+    ) -> BlockEntropies
+    where
+        Y: Ord + Clone,
+    {
+        self.observed_block_entropies_bounded(observable, stationary, n, usize::MAX)
+    }
+
+    /// Same as `observed_block_entropies`, but stops before any block
+    /// length that could need more than `max_words` words. The length
+    /// reached is `entropies.len()`, at least one.
+    pub fn observed_block_entropies_bounded<Y>(
+        &self,
+        observable: &Markov<X, Y>,
+        stationary: &Prob<X>,
+        n: usize,
+        max_words: usize,
+    ) -> BlockEntropies
+    where
+        Y: Ord + Clone,
+    {
+        let mut words = ObservedWords::new(self, observable, stationary);
+        let mut entropies = Vec::with_capacity(n);
+
+        for k in 0..n {
+            let entropy = words
+                .probabilities()
+                .filter(|(_, p)| *p > 0.0)
+                .map(|(_, p)| -p * p.ln())
+                .sum();
+            entropies.push(entropy);
+
+            if k + 1 == n || words.extended_len_bound() > max_words {
+                break;
+            }
+            words.extend();
+        }
+
+        BlockEntropies { entropies }
+    }
+
+    /// Relative entropy rate Σ_x π_x Σ_y P_xy ln(P_xy / Q_xy) of this chain
+    /// with respect to `other`, which must have the same labels. Infinite
+    /// when this chain uses a transition that `other` forbids.
+    pub fn relative_entropy_rate(
+        &self,
+        other: &Markov<X, X>,
+        stationary: &Prob<X>,
+    ) -> Result<f64, BuildError> {
+        if self.matrix.x_ix_map != other.matrix.x_ix_map
+            || self.matrix.y_ix_map != other.matrix.y_ix_map
+        {
+            return Err(BuildError::LabelMismatch);
+        }
+
+        let q = other.matrix.values.to_csr();
+        let mut total = 0.0;
+        for (&p, (i, j)) in self.matrix.values.iter() {
+            let pi = stationary.vector.values[i];
+            if p <= 0.0 || pi <= 0.0 {
+                continue;
+            }
+            let q_ij = q.get(i, j).copied().unwrap_or(0.0);
+            if q_ij <= 0.0 {
+                return Ok(f64::INFINITY);
+            }
+            total += pi * p * (p / q_ij).ln();
+        }
+
+        Ok(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Matrix, Vector};
+
+    #[test]
+    fn test_markov_block_entropies_match_observed_identity() {
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "a", 1.0),
+            ("a", "b", 1.0),
+            ("b", "a", 2.0),
+            ("b", "c", 1.0),
+            ("c", "a", 1.0),
+        ]))
+        .unwrap();
+        let pi = markov.stationary_direct(1e-12).unwrap().distribution;
+        let identity =
+            Markov::from_matrix(Matrix::from_assoc(["a", "b", "c"].map(|x| (x, x, 1.0)))).unwrap();
+
+        let exact = markov.block_entropies(&pi, 4);
+        let enumerated = markov.observed_block_entropies(&identity, &pi, 4);
+        for (e, w) in exact.entropies.iter().zip(&enumerated.entropies) {
+            assert!((e - w).abs() < 1e-12);
+        }
+        let mi = markov.mutual_information(&pi);
+        assert!((exact.excess_entropy().unwrap() - mi).abs() < 1e-12);
+        assert!((exact.mutual_information().unwrap() - mi).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_coarse_graining_can_increase_effective_information() {
+        // Hoel's example: a, b, c mix uniformly among themselves and d is
+        // fixed. Grouping {a, b, c} gives a deterministic two-state chain.
+        let micro = Markov::from_matrix(Matrix::from_assoc(
+            ["a", "b", "c"]
+                .iter()
+                .flat_map(|x| ["a", "b", "c"].map(|y| (*x, y, 1.0)))
+                .chain([("d", "d", 1.0)]),
+        ))
+        .unwrap();
+        let observable = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "A", 1.0),
+            ("b", "A", 1.0),
+            ("c", "A", 1.0),
+            ("d", "D", 1.0),
+        ]))
+        .unwrap();
+        let uniform =
+            Prob::from_vector(Vector::from_assoc(["a", "b", "c", "d"].map(|x| (x, 1.0)))).unwrap();
+        let macro_chain = micro.induced_kernel(&observable, &uniform).unwrap();
+
+        let micro_ei = micro.effective_information().unwrap();
+        let (ln2, ln3) = (2.0_f64.ln(), 3.0_f64.ln());
+        assert!((micro_ei.determinism - (2.0 * ln2 - 0.75 * ln3)).abs() < 1e-12);
+        assert!(micro_ei.degeneracy.abs() < 1e-12);
+
+        let macro_ei = macro_chain.effective_information().unwrap();
+        assert!((macro_ei.effective_information - ln2).abs() < 1e-12);
+        assert!((macro_ei.effectiveness - 1.0).abs() < 1e-12);
+        assert!(macro_ei.effective_information > micro_ei.effective_information);
+
+        // Without mass on d, D has no row but is still a column
+        let on_abc =
+            Prob::from_vector(Vector::from_assoc(["a", "b", "c"].map(|x| (x, 1.0)))).unwrap();
+        let partial = micro.induced_kernel(&observable, &on_abc).unwrap();
+        assert!(matches!(
+            partial.effective_information(),
+            Err(BuildError::LabelMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_lumped_process_has_memory() {
+        // Deterministic 3-cycle a -> b -> c -> a seen through {a, b} -> 0, c -> 1
+        let cycle = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "b", 1.0),
+            ("b", "c", 1.0),
+            ("c", "a", 1.0),
+        ]))
+        .unwrap();
+        let pi = Prob::from_vector(Vector::from_assoc(vec![("a", 1.0), ("b", 1.0), ("c", 1.0)]))
+            .unwrap();
+        let observable = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", 0, 1.0),
+            ("b", 0, 1.0),
+            ("c", 1, 1.0),
+        ]))
+        .unwrap();
+
+        let blocks = cycle.observed_block_entropies(&observable, &pi, 4);
+        // The observed sequence 0 0 1 0 0 1 ... is determined by its phase
+        assert!((blocks.entropies[3] - 3.0_f64.ln()).abs() < 1e-12);
+
+        // Two symbols: a budget of 4 words allows blocks of length 2 only
+        let bounded = cycle.observed_block_entropies_bounded(&observable, &pi, 4, 4);
+        assert_eq!(bounded.entropies, blocks.entropies[..2]);
+        assert!(blocks.entropy_rate().unwrap().abs() < 1e-12);
+        assert!((blocks.excess_entropy().unwrap() - 3.0_f64.ln()).abs() < 1e-12);
+
+        let uniform = Markov::from_matrix(Matrix::from_assoc(
+            ["a", "b", "c"]
+                .iter()
+                .flat_map(|x| ["a", "b", "c"].map(|y| (*x, y, 1.0))),
+        ))
+        .unwrap();
+        let divergence = cycle.relative_entropy_rate(&uniform, &pi).unwrap();
+        assert!((divergence - 3.0_f64.ln()).abs() < 1e-12);
+        assert_eq!(
+            uniform.relative_entropy_rate(&cycle, &pi).unwrap(),
+            f64::INFINITY
+        );
+    }
+}
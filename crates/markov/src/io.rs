@@ -0,0 +1,583 @@
+//! Exchange formats: Matrix Market coordinate files, long-form CSV and
+//! dense NumPy `.npy` arrays.
+//!
+//! CSV keeps the labels. Matrix Market and `.npy` only know positions, so
+//! matrices are written in label order and read back with the positions as
+//! labels: 1-based for Matrix Market, 0-based for `.npy`, zero-padded so
+//! that the labels sort in file order.
+
+use std::fmt::Display;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::sync::Arc;
+
+use ndarray::Array1;
+use sprs::TriMat;
+
+use crate::ix_map::IxMap;
+use crate::matrix::Matrix;
+use crate::vector::Vector;
+
+const NPY_MAGIC: &[u8] = b"\x93NUMPY";
+
+//##########################################################
+// Matrix
+//##########################################################
+
+impl Matrix<String, String> {
+    /// Read a Matrix Market `coordinate` file with `real`, `integer` or
+    /// `pattern` entries and `general` or `symmetric` layout. Repeated
+    /// entries are added up.
+    pub fn read_matrix_market(reader: impl BufRead) -> Result<Self, FormatError> {
+        let mut lines = reader.lines().enumerate();
+
+        let (_, header) = lines.next().ok_or(FormatError::Empty)?;
+        let header = header?.to_lowercase();
+        let fields: Vec<&str> = header.split_whitespace().collect();
+        if fields.len() != 5 || fields[0] != "%%matrixmarket" || fields[1] != "matrix" {
+            return Err(parse_error(1, "missing %%MatrixMarket matrix header"));
+        }
+        if fields[2] != "coordinate" {
+            return Err(FormatError::Unsupported(fields[2].to_string()));
+        }
+        let pattern = match fields[3] {
+            "real" | "integer" => false,
+            "pattern" => true,
+            field => return Err(FormatError::Unsupported(field.to_string())),
+        };
+        let symmetric = match fields[4] {
+            "general" => false,
+            "symmetric" => true,
+            symmetry => return Err(FormatError::Unsupported(symmetry.to_string())),
+        };
+
+        let mut content = lines.filter_map(|(i, line)| match line {
+            Ok(line) if line.trim().is_empty() || line.starts_with('%') => None,
+            line => Some((i + 1, line)),
+        });
+
+        let (number, size) = content.next().ok_or(FormatError::Empty)?;
+        let size = parse_fields::<usize>(number, &size?)?;
+        let [rows, cols, entries] = size[..] else {
+            return Err(parse_error(number, "expected `rows cols entries`"));
+        };
+
+        let mut trimat = TriMat::new((rows, cols));
+        let mut last = number;
+        for _ in 0..entries {
+            let (number, line) = content
+                .next()
+                .ok_or_else(|| parse_error(last, "fewer entries than announced"))?;
+            last = number;
+            let line = line?;
+            let mut fields = line.split_whitespace();
+            let mut index = |bound: usize| -> Result<usize, FormatError> {
+                let k: usize = parse_field(number, fields.next())?;
+                if k == 0 || k > bound {
+                    return Err(parse_error(number, "index out of bounds"));
+                }
+                Ok(k - 1)
+            };
+            let (i, j) = (index(rows)?, index(cols)?);
+            let value = if pattern {
+                1.0
+            } else {
+                parse_field(number, fields.next())?
+            };
+            trimat.add_triplet(i, j, value);
+            if symmetric && i != j {
+                trimat.add_triplet(j, i, value);
+            }
+        }
+
+        if let Some((number, _)) = content.next() {
+            return Err(parse_error(number, "more entries than announced"));
+        }
+        Ok(positional(trimat, 1))
+    }
+
+    /// Read long-form CSV with one `row_label,col_label,value` line per
+    /// entry. A first line whose value is not a number is taken as a header.
+    pub fn read_csv(reader: impl BufRead) -> Result<Self, FormatError> {
+        let mut entries = Vec::new();
+        for (number, fields) in csv_records(reader)? {
+            let [row, col, value] = <[String; 3]>::try_from(fields)
+                .map_err(|_| parse_error(number, "expected `row,col,value`"))?;
+            match value.trim().parse::<f64>() {
+                Ok(value) => entries.push((row, col, value)),
+                Err(_) if number == 1 => continue,
+                Err(e) => return Err(parse_error(number, e)),
+            }
+        }
+        Ok(Matrix::from_assoc(entries))
+    }
+
+    /// Read a two-dimensional `.npy` array of `f8` or `f4` values.
+    pub fn read_npy(reader: impl Read) -> Result<Self, FormatError> {
+        let (shape, values) = read_npy(reader)?;
+        let [rows, cols] = shape[..] else {
+            return Err(FormatError::Unsupported(format!(
+                "{}-dimensional array",
+                shape.len()
+            )));
+        };
+
+        let mut trimat = TriMat::new((rows, cols));
+        for (k, &v) in values.iter().enumerate().filter(|(_, v)| **v != 0.0) {
+            trimat.add_triplet(k / cols, k % cols, v);
+        }
+        Ok(positional(trimat, 0))
+    }
+}
+
+impl<X, Y> Matrix<X, Y>
+where
+    X: Ord + Clone,
+    Y: Ord + Clone,
+{
+    /// Write as a `real general` Matrix Market coordinate file, with rows
+    /// and columns in label order.
+    pub fn write_matrix_market(&self, mut writer: impl Write) -> Result<(), FormatError> {
+        let (rows, cols) = self.values.shape();
+        writeln!(writer, "%%MatrixMarket matrix coordinate real general")?;
+        writeln!(writer, "{} {} {}", rows, cols, self.values.nnz())?;
+        for (i, j, v) in self.row_major() {
+            writeln!(writer, "{} {} {}", i + 1, j + 1, v)?;
+        }
+        Ok(())
+    }
+
+    /// Write as a dense little-endian `f8` `.npy` array, with rows and
+    /// columns in label order.
+    pub fn write_npy(&self, writer: impl Write) -> Result<(), FormatError> {
+        let (rows, cols) = self.values.shape();
+        let mut values = vec![0.0; rows * cols];
+        for (&v, (i, j)) in self.values.iter() {
+            values[i * cols + j] += v;
+        }
+        write_npy(writer, &[rows, cols], &values)
+    }
+
+    /// Write as long-form CSV with a `row,col,value` header.
+    pub fn write_csv(&self, mut writer: impl Write) -> Result<(), FormatError>
+    where
+        X: Display,
+        Y: Display,
+    {
+        writeln!(writer, "row,col,value")?;
+        for (i, j, v) in self.row_major() {
+            if let (Some(x), Some(y)) = (self.x_ix_map.value_of(i), self.y_ix_map.value_of(j)) {
+                writeln!(writer, "{},{},{}", csv_field(x), csv_field(y), v)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn row_major(&self) -> Vec<(usize, usize, f64)> {
+        let mut entries: Vec<(usize, usize, f64)> =
+            self.values.iter().map(|(&v, (i, j))| (i, j, v)).collect();
+        entries.sort_by_key(|&(i, j, _)| (i, j));
+        entries
+    }
+}
+
+//##########################################################
+// Vector
+//##########################################################
+
+impl Vector<String> {
+    /// Read `label,value` lines. A first line whose value is not a number
+    /// is taken as a header.
+    pub fn read_csv(reader: impl BufRead) -> Result<Self, FormatError> {
+        let mut entries = Vec::new();
+        for (number, fields) in csv_records(reader)? {
+            let [label, value] = <[String; 2]>::try_from(fields)
+                .map_err(|_| parse_error(number, "expected `label,value`"))?;
+            match value.trim().parse::<f64>() {
+                Ok(value) => entries.push((label, value)),
+                Err(_) if number == 1 => continue,
+                Err(e) => return Err(parse_error(number, e)),
+            }
+        }
+        Ok(Vector::from_assoc(entries))
+    }
+
+    /// Read a one-dimensional `.npy` array of `f8` or `f4` values.
+    pub fn read_npy(reader: impl Read) -> Result<Self, FormatError> {
+        let (shape, values) = read_npy(reader)?;
+        if shape.len() != 1 {
+            return Err(FormatError::Unsupported(format!(
+                "{}-dimensional array",
+                shape.len()
+            )));
+        }
+        Ok(Vector {
+            ix_map: Arc::new(position_labels(values.len(), 0)),
+            values: Array1::from(values),
+        })
+    }
+}
+
+impl<X> Vector<X>
+where
+    X: Ord + Clone,
+{
+    /// Write as a one-dimensional little-endian `f8` `.npy` array, in label
+    /// order.
+    pub fn write_npy(&self, writer: impl Write) -> Result<(), FormatError> {
+        write_npy(writer, &[self.len()], &self.values.to_vec())
+    }
+
+    /// Write `label,value` lines with a header.
+    pub fn write_csv(&self, mut writer: impl Write) -> Result<(), FormatError>
+    where
+        X: Display,
+    {
+        writeln!(writer, "label,value")?;
+        for (x, v) in self.enumerate() {
+            writeln!(writer, "{},{}", csv_field(&x), v)?;
+        }
+        Ok(())
+    }
+}
+
+//##########################################################
+// Helpers
+//##########################################################
+
+// Labels for positions 0..n, shifted by `base` and zero-padded so they
+// sort in numeric order
+fn position_labels(n: usize, base: usize) -> IxMap<String> {
+    let width = (n + base).saturating_sub(1).max(1).to_string().len();
+    IxMap::from_distinct_sorted((0..n).map(|k| format!("{:0width$}", k + base)))
+}
+
+fn positional(trimat: TriMat<f64>, base: usize) -> Matrix<String, String> {
+    let (rows, cols) = trimat.shape();
+    Matrix {
+        values: trimat.to_csc(),
+        x_ix_map: Arc::new(position_labels(rows, base)),
+        y_ix_map: Arc::new(position_labels(cols, base)),
+    }
+}
+
+fn parse_error(line: usize, message: impl Display) -> FormatError {
+    FormatError::Parse {
+        line,
+        message: message.to_string(),
+    }
+}
+
+fn parse_field<T>(line: usize, field: Option<&str>) -> Result<T, FormatError>
+where
+    T: std::str::FromStr,
+    T::Err: Display,
+{
+    field
+        .ok_or_else(|| parse_error(line, "missing field"))?
+        .parse()
+        .map_err(|e| parse_error(line, e))
+}
+
+fn parse_fields<T>(line: usize, text: &str) -> Result<Vec<T>, FormatError>
+where
+    T: std::str::FromStr,
+    T::Err: Display,
+{
+    text.split_whitespace()
+        .map(|field| parse_field(line, Some(field)))
+        .collect()
+}
+
+// Non-empty CSV records with the 1-based line numbers they start on.
+// Fields may be wrapped in double quotes, with `""` for a literal quote;
+// quoted fields may span lines.
+fn csv_records(reader: impl BufRead) -> Result<Vec<(usize, Vec<String>)>, FormatError> {
+    let mut records = Vec::new();
+    // A record whose last field is still inside quotes at the end of a line
+    let mut open: Option<(usize, Vec<String>)> = None;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim_end_matches('\r');
+        let (quoted, number, mut fields) = match open.take() {
+            Some((number, mut fields)) => {
+                fields.last_mut().expect("at least one field").push('\n');
+                (true, number, fields)
+            }
+            None if line.trim().is_empty() => continue,
+            None => (false, i + 1, vec![String::new()]),
+        };
+
+        let mut quoted = quoted;
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            let field = fields.last_mut().expect("at least one field");
+            match c {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = !quoted,
+                ',' if !quoted => fields.push(String::new()),
+                c => field.push(c),
+            }
+        }
+        if quoted {
+            open = Some((number, fields));
+        } else {
+            records.push((number, fields));
+        }
+    }
+    if let Some((number, _)) = open {
+        return Err(parse_error(number, "unterminated quote"));
+    }
+    Ok(records)
+}
+
+fn csv_field(label: &impl Display) -> String {
+    let label = label.to_string();
+    if label.contains([',', '"', '\n']) {
+        format!("\"{}\"", label.replace('"', "\"\""))
+    } else {
+        label
+    }
+}
+
+// Shape and values in C order
+fn read_npy(reader: impl Read) -> Result<(Vec<usize>, Vec<f64>), FormatError> {
+    // Buffered, since the data below is read entry by entry
+    let mut reader = BufReader::new(reader);
+    let mut preamble = [0u8; 8];
+    reader.read_exact(&mut preamble)?;
+    if &preamble[..6] != NPY_MAGIC {
+        return Err(FormatError::Unsupported("not an .npy file".to_string()));
+    }
+    let header_len = match preamble[6] {
+        1 => {
+            let mut len = [0u8; 2];
+            reader.read_exact(&mut len)?;
+            u16::from_le_bytes(len) as usize
+        }
+        2 | 3 => {
+            let mut len = [0u8; 4];
+            reader.read_exact(&mut len)?;
+            u32::from_le_bytes(len) as usize
+        }
+        version => return Err(FormatError::Unsupported(format!("npy version {version}"))),
+    };
+    let mut header = vec![0u8; header_len];
+    reader.read_exact(&mut header)?;
+    let header = String::from_utf8_lossy(&header);
+
+    let entry = |key: &str| -> Result<&str, FormatError> {
+        let start = header
+            .find(&format!("'{key}'"))
+            .ok_or_else(|| parse_error(1, format!("missing '{key}' in header")))?;
+        let rest = header[start + key.len() + 2..].trim_start();
+        Ok(rest.strip_prefix(':').unwrap_or(rest).trim_start())
+    };
+
+    let descr = entry("descr")?;
+    let width = if descr.starts_with("'<f8'") || descr.starts_with("'float64'") {
+        8
+    } else if descr.starts_with("'<f4'") || descr.starts_with("'float32'") {
+        4
+    } else {
+        let end = descr.find(',').unwrap_or(descr.len());
+        return Err(FormatError::Unsupported(format!("dtype {}", &descr[..end])));
+    };
+    let fortran = entry("fortran_order")?.starts_with("True");
+
+    let shape = entry("shape")?;
+    let (dims, _) = shape
+        .strip_prefix('(')
+        .and_then(|rest| rest.split_once(')'))
+        .ok_or_else(|| parse_error(1, "malformed shape"))?;
+    let shape: Vec<usize> = dims
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().map_err(|e| parse_error(1, e)))
+        .collect::<Result<_, _>>()?;
+
+    let count = shape
+        .iter()
+        .try_fold(1usize, |count, &n| count.checked_mul(n))
+        .filter(|count| count.checked_mul(width).is_some())
+        .ok_or_else(|| parse_error(1, "shape is too large"))?;
+    // Read entry by entry, so a header promising more data than the input
+    // holds fails on the read rather than on the allocation
+    let mut values = Vec::new();
+    let mut chunk = [0u8; 8];
+    for _ in 0..count {
+        let chunk = &mut chunk[..width];
+        reader.read_exact(chunk)?;
+        values.push(match width {
+            8 => f64::from_le_bytes(chunk.try_into().expect("eight bytes")),
+            _ => f32::from_le_bytes(chunk.try_into().expect("four bytes")) as f64,
+        });
+    }
+
+    if fortran && shape.len() == 2 {
+        let (rows, cols) = (shape[0], shape[1]);
+        values = (0..rows * cols)
+            .map(|k| values[(k % cols) * rows + k / cols])
+            .collect();
+    }
+    Ok((shape, values))
+}
+
+fn write_npy(mut writer: impl Write, shape: &[usize], values: &[f64]) -> Result<(), FormatError> {
+    let shape = match shape {
+        [n] => format!("({n},)"),
+        _ => format!(
+            "({})",
+            shape
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    let mut header = format!("{{'descr': '<f8', 'fortran_order': False, 'shape': {shape}, }}");
+    // Magic, version and length take 10 bytes; the data starts 64-aligned
+    let padding = 63 - (10 + header.len()) % 64;
+    header.extend(std::iter::repeat_n(' ', padding));
+    header.push('\n');
+
+    writer.write_all(NPY_MAGIC)?;
+    writer.write_all(&[1, 0])?;
+    writer.write_all(&(header.len() as u16).to_le_bytes())?;
+    writer.write_all(header.as_bytes())?;
+    for v in values {
+        writer.write_all(&v.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum FormatError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("input is empty")]
+    Empty,
+    #[error("line {line}: {message}")]
+    Parse { line: usize, message: String },
+    #[error("unsupported format: {0}")]
+    Unsupported(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Matrix<String, String> {
+        Matrix::from_assoc(vec![
+            ("a".to_string(), "a".to_string(), 0.5),
+            ("a".to_string(), "b, c".to_string(), 0.5),
+            ("b, c".to_string(), "a".to_string(), 1.0),
+        ])
+    }
+
+    #[test]
+    fn test_csv_round_trip_keeps_labels() {
+        let mut bytes = Vec::new();
+        example().write_csv(&mut bytes).unwrap();
+        let back = Matrix::read_csv(bytes.as_slice()).unwrap();
+        assert_eq!(back.get(&"a".to_string(), &"b, c".to_string()), Some(0.5));
+        assert_eq!(back.get(&"b, c".to_string(), &"a".to_string()), Some(1.0));
+
+        let vector = Vector::read_csv("label,value\nx,1.5\n\"y\",2\n".as_bytes()).unwrap();
+        assert_eq!(vector.get(&"y".to_string()), Some(2.0));
+    }
+
+    #[test]
+    fn test_positional_formats_round_trip() {
+        let matrix = example();
+
+        let mut market = Vec::new();
+        matrix.write_matrix_market(&mut market).unwrap();
+        let back = Matrix::read_matrix_market(market.as_slice()).unwrap();
+        assert_eq!(back.get(&"1".to_string(), &"2".to_string()), Some(0.5));
+        assert_eq!(back.get(&"2".to_string(), &"1".to_string()), Some(1.0));
+
+        let mut npy = Vec::new();
+        matrix.write_npy(&mut npy).unwrap();
+        // The 2 × 2 f8 data starts on a 64-byte boundary
+        assert_eq!((npy.len() - 32) % 64, 0);
+        let back = Matrix::read_npy(npy.as_slice()).unwrap();
+        assert_eq!(back.get(&"0".to_string(), &"1".to_string()), Some(0.5));
+        assert_eq!(back.values.nnz(), 3);
+
+        let symmetric =
+            "%%MatrixMarket matrix coordinate pattern symmetric\n% comment\n12 12 1\n12 1\n";
+        let back = Matrix::read_matrix_market(symmetric.as_bytes()).unwrap();
+        assert_eq!(back.get(&"01".to_string(), &"12".to_string()), Some(1.0));
+        assert_eq!(back.x_ix_map.value_of(11), Some(&"12".to_string()));
+    }
+
+    fn npy_with_header(header: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = NPY_MAGIC.to_vec();
+        bytes.extend([1, 0]);
+        bytes.extend((header.len() as u16).to_le_bytes());
+        bytes.extend(header.as_bytes());
+        bytes.extend(data);
+        bytes
+    }
+
+    #[test]
+    fn test_multiline_labels_round_trip() {
+        let vector = Vector::from_assoc(vec![("x\ny".to_string(), 1.5), ("z".to_string(), 2.0)]);
+        let mut bytes = Vec::new();
+        vector.write_csv(&mut bytes).unwrap();
+        let back = Vector::read_csv(bytes.as_slice()).unwrap();
+        assert_eq!(back.get(&"x\ny".to_string()), Some(1.5));
+        assert_eq!(back.get(&"z".to_string()), Some(2.0));
+
+        let error = Vector::read_csv("label,value\n\"x,1\n".as_bytes()).unwrap_err();
+        assert!(matches!(error, FormatError::Parse { line: 2, .. }));
+    }
+
+    #[test]
+    fn test_matrix_market_entry_count_is_checked() {
+        let header = "%%MatrixMarket matrix coordinate real general\n2 2 2\n";
+
+        let short = format!("{header}1 1 0.5\n% trailing comment\n");
+        let error = Matrix::read_matrix_market(short.as_bytes()).unwrap_err();
+        assert!(
+            matches!(error, FormatError::Parse { line: 3, .. }),
+            "{error}"
+        );
+
+        let long = format!("{header}1 1 0.5\n2 2 1\n1 2 0.5\n");
+        let error = Matrix::read_matrix_market(long.as_bytes()).unwrap_err();
+        assert!(
+            matches!(error, FormatError::Parse { line: 5, .. }),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_malformed_npy_headers_are_rejected() {
+        let header = |shape: &str| {
+            format!("{{'descr': '<f8', 'fortran_order': False, 'shape': {shape}, }}\n")
+        };
+        for shape in ["2, 2)", "é2, 2)", "(2, 2", "(2, x)"] {
+            let bytes = npy_with_header(&header(shape), &[0; 32]);
+            let error = Matrix::read_npy(bytes.as_slice()).unwrap_err();
+            assert!(
+                matches!(error, FormatError::Parse { .. }),
+                "{shape}: {error}"
+            );
+        }
+
+        let huge = format!("({}, {})", usize::MAX, 2);
+        let bytes = npy_with_header(&header(&huge), &[]);
+        let error = Matrix::read_npy(bytes.as_slice()).unwrap_err();
+        assert!(matches!(error, FormatError::Parse { .. }));
+
+        // Fits in a usize, but the input holds far less data
+        let bytes = npy_with_header(&header("(1000000000000,)"), &[0; 8]);
+        let error = Vector::read_npy(bytes.as_slice()).unwrap_err();
+        assert!(matches!(error, FormatError::Io(_)));
+    }
+}
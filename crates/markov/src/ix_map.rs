@@ -1,6 +1,6 @@
 /// Simple bidirectional map between values and indices.
 /// Values are stored in sorted order and lookups use binary search.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct IxMap<T> {
     values: Vec<T>,
 }
@@ -28,6 +28,42 @@ impl<T: Ord + Clone> IxMap<T> {
         }
     }
 
+    /// Sorted labels present in either map.
+    pub fn union(&self, other: &IxMap<T>) -> IxMap<T> {
+        let mut values = Vec::with_capacity(self.len().max(other.len()));
+        let (mut a, mut b) = (
+            self.values.iter().peekable(),
+            other.values.iter().peekable(),
+        );
+        loop {
+            let next = match (a.peek(), b.peek()) {
+                (Some(x), Some(y)) if x < y => a.next(),
+                (Some(x), Some(y)) if x > y => b.next(),
+                (Some(_), Some(_)) => {
+                    b.next();
+                    a.next()
+                }
+                (Some(_), None) => a.next(),
+                (None, Some(_)) => b.next(),
+                (None, None) => break,
+            };
+            values.extend(next.cloned());
+        }
+        Self { values }
+    }
+
+    /// Sorted labels present in both maps.
+    pub fn intersection(&self, other: &IxMap<T>) -> IxMap<T> {
+        Self {
+            values: self
+                .values
+                .iter()
+                .filter(|x| other.index_of(x).is_some())
+                .cloned()
+                .collect(),
+        }
+    }
+
     /// Iterator over all (index, value) pairs
     pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
         self.values.iter().enumerate()
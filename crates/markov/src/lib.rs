@@ -1,11 +1,50 @@
+pub mod absorbing;
+pub mod classes;
+pub mod entropy_production;
+pub mod estimate;
+pub mod generator;
+pub mod higher_order;
+pub mod hitting;
+pub mod hmm;
+pub mod information;
+pub mod io;
 pub mod ix_map;
+pub mod linalg;
+pub mod lumpability;
 pub mod markov;
 pub mod matrix;
+pub mod pcca;
 pub mod prob;
+pub mod reward;
+pub mod sample;
+pub mod scalar;
+#[cfg(feature = "serde")]
+mod serialize;
+pub mod spectral;
+pub mod stationary;
 pub mod vector;
 
+pub use absorbing::{Absorption, AbsorptionError};
+pub use classes::{ClassDecomposition, CommunicatingClass};
+pub use entropy_production::EntropyProduction;
+pub use estimate::{EstimationError, Posterior, ReversibleEstimate, TransitionCounts};
+pub use generator::{Generator, GeneratorError, Transient};
+pub use higher_order::HigherOrderKernel;
+pub use hitting::{HittingError, HittingTimes};
+pub use hmm::{BaumWelch, Filtering, Hmm, HmmError, Smoothing, ViterbiPath};
+pub use information::{BlockEntropies, EffectiveInformation};
+pub use io::FormatError;
 pub use ix_map::IxMap;
+pub use lumpability::Lumpability;
 pub use markov::Markov;
 pub use matrix::Matrix;
+pub use pcca::{Coarsening, CoarseningError};
 pub use prob::{BuildError, Prob};
-pub use vector::Vector;
+pub use reward::{AverageReward, RewardError, RewardProcess};
+pub use sample::{AliasTable, JointTrajectory, KernelSampler, Sampler, Trajectory};
+pub use scalar::Scalar;
+#[cfg(feature = "serde")]
+pub use serialize::DeserializeError;
+pub use spectral::{Eigenpair, Eigenvector, Spectrum};
+pub use stationary::{Stationary, StationaryMethod};
+pub use vector::{Alignment, AlignmentError, Vector};
@@ -0,0 +1,187 @@
+use ndarray::Array1;
+use sprs::CsMat;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Pivots smaller than this are treated as zero.
+const PIVOT_TOLERANCE: f64 = 1e-12;
+
+/// Sparse LU factorization of a square matrix, computed by Gaussian
+/// elimination with partial pivoting on a row-map representation.
+/// The factorization can be reused to solve for many right-hand sides.
+#[derive(Debug, Clone)]
+pub struct SparseLu {
+    size: usize,
+    /// Row chosen as pivot for each column.
+    pivots: Vec<usize>,
+    /// Elimination steps: for each column, the (row, factor) pairs applied.
+    eliminations: Vec<Vec<(usize, f64)>>,
+    /// Upper triangular part, stored by original row index.
+    upper: Vec<BTreeMap<usize, f64>>,
+}
+
+impl SparseLu {
+    /// Factorize a square sparse matrix (any storage order).
+    pub fn factorize(matrix: &CsMat<f64>) -> Result<Self, SolveError> {
+        let (nrows, ncols) = matrix.shape();
+        if nrows != ncols {
+            return Err(SolveError::NotSquare);
+        }
+        let size = nrows;
+
+        let mut rows: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); size];
+        let mut cols: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); size];
+
+        for (&val, (row, col)) in matrix.iter() {
+            if val != 0.0 {
+                *rows[row].entry(col).or_insert(0.0) += val;
+                cols[col].insert(row);
+            }
+        }
+
+        let mut pivoted = vec![false; size];
+        let mut pivots = Vec::with_capacity(size);
+        let mut eliminations = Vec::with_capacity(size);
+
+        for k in 0..size {
+            let pivot = cols[k]
+                .iter()
+                .filter(|&&r| !pivoted[r])
+                .map(|&r| (r, rows[r].get(&k).copied().unwrap_or(0.0)))
+                .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()));
+
+            let (p, pivot_value) = match pivot {
+                Some((p, v)) if v.abs() > PIVOT_TOLERANCE => (p, v),
+                _ => return Err(SolveError::Singular),
+            };
+
+            pivoted[p] = true;
+            pivots.push(p);
+
+            let targets: Vec<usize> = cols[k].iter().copied().filter(|&r| !pivoted[r]).collect();
+            let pivot_row: Vec<(usize, f64)> = rows[p].iter().map(|(&c, &v)| (c, v)).collect();
+            let mut step = Vec::with_capacity(targets.len());
+
+            for r in targets {
+                let factor = rows[r].remove(&k).unwrap_or(0.0) / pivot_value;
+                cols[k].remove(&r);
+                if factor == 0.0 {
+                    continue;
+                }
+
+                for &(c, v) in pivot_row.iter().filter(|(c, _)| *c != k) {
+                    let entry = rows[r].entry(c).or_insert(0.0);
+                    *entry -= factor * v;
+                    if *entry == 0.0 {
+                        rows[r].remove(&c);
+                        cols[c].remove(&r);
+                    } else {
+                        cols[c].insert(r);
+                    }
+                }
+
+                step.push((r, factor));
+            }
+
+            eliminations.push(step);
+        }
+
+        Ok(Self {
+            size,
+            pivots,
+            eliminations,
+            upper: rows,
+        })
+    }
+
+    /// Dimension of the factorized matrix.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Solve A x = b using the stored factorization.
+    pub fn solve(&self, rhs: &Array1<f64>) -> Result<Array1<f64>, SolveError> {
+        if rhs.len() != self.size {
+            return Err(SolveError::DimensionMismatch);
+        }
+
+        let mut b = rhs.clone();
+        for (k, step) in self.eliminations.iter().enumerate() {
+            let bp = b[self.pivots[k]];
+            for &(r, factor) in step {
+                b[r] -= factor * bp;
+            }
+        }
+
+        let mut x = Array1::zeros(self.size);
+        for k in (0..self.size).rev() {
+            let row = &self.upper[self.pivots[k]];
+            let mut acc = b[self.pivots[k]];
+            for (&c, &v) in row.range(k + 1..) {
+                acc -= v * x[c];
+            }
+            x[k] = acc / row[&k];
+        }
+
+        Ok(x)
+    }
+}
+
+/// Solve A x = b for a square sparse matrix A.
+pub fn solve(matrix: &CsMat<f64>, rhs: &Array1<f64>) -> Result<Array1<f64>, SolveError> {
+    SparseLu::factorize(matrix)?.solve(rhs)
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum SolveError {
+    #[error("matrix is not square")]
+    NotSquare,
+    #[error("matrix is singular")]
+    Singular,
+    #[error("right-hand side has the wrong dimension")]
+    DimensionMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sprs::TriMat;
+
+    #[test]
+    fn test_solve_needs_pivoting() {
+        // [0 2 1] [x]   [7]
+        // [1 1 0] [y] = [3]
+        // [3 0 1] [z]   [6]
+        let mut tri = TriMat::new((3, 3));
+        for (i, j, v) in [
+            (0, 1, 2.0),
+            (0, 2, 1.0),
+            (1, 0, 1.0),
+            (1, 1, 1.0),
+            (2, 0, 3.0),
+            (2, 2, 1.0),
+        ] {
+            tri.add_triplet(i, j, v);
+        }
+        let a: CsMat<f64> = tri.to_csr();
+        let b = Array1::from(vec![7.0, 3.0, 6.0]);
+
+        let x = solve(&a, &b).unwrap();
+
+        // Solution is (1, 2, 3)
+        assert!((x[0] - 1.0).abs() < 1e-12);
+        assert!((x[1] - 2.0).abs() < 1e-12);
+        assert!((x[2] - 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_singular_matrix_is_rejected() {
+        let mut tri = TriMat::new((2, 2));
+        tri.add_triplet(0, 0, 1.0);
+        tri.add_triplet(0, 1, 2.0);
+        tri.add_triplet(1, 0, 2.0);
+        tri.add_triplet(1, 1, 4.0);
+        let a: CsMat<f64> = tri.to_csr();
+
+        assert!(matches!(SparseLu::factorize(&a), Err(SolveError::Singular)));
+    }
+}
@@ -0,0 +1,277 @@
+use ndarray::linalg::Dot;
+use sprs::vec::{CsVecView, NnzEither, SparseIterTools};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use crate::markov::{BuildError, Markov};
+use crate::matrix::Matrix;
+use crate::prob::Prob;
+use crate::scalar::Scalar;
+use crate::vector::Vector;
+
+/// How far an observable F is from intertwining the micro kernel P with
+/// its induced macro kernel Φ, i.e. from PF = FΦ.
+#[derive(Debug, Clone)]
+pub struct Lumpability<X, Y, S = f64> {
+    /// Macro kernel Φ_yy' = Σ_x μ_x F_xy (PF)_xy' / Σ_x μ_x F_xy induced by
+    /// the reference measure μ. Blocks without mass have no row.
+    pub induced: Markov<Y, Y, S>,
+    /// Block-transition rows (PF)_xy'.
+    pub block_rows: Matrix<X, Y, S>,
+    /// Largest |(PF)_xy' − Φ_yy'| over the states x with F_xy > 0. Pairs
+    /// where both vanish for every such state have no entry.
+    pub block_deviation: Matrix<Y, Y, S>,
+    /// Row norm ‖(PF − FΦ)_x·‖₁ of each micro state.
+    pub state_deviation: Vector<X, S>,
+    /// Σ_x μ_x ‖(PF − FΦ)_x·‖₁.
+    pub intertwining_error: S,
+}
+
+impl<X, Y, S> Lumpability<X, Y, S>
+where
+    X: Ord + Clone,
+    Y: Ord + Clone,
+    S: Scalar,
+{
+    /// Every state of a block has the same block-transition row, up to
+    /// `tolerance`. For a deterministic partition this is strong
+    /// lumpability: the lumped process is Markov from any initial law.
+    /// With exact scalars a zero tolerance gives an exact answer.
+    pub fn is_strongly_lumpable(&self, tolerance: S) -> bool {
+        self.max_block_deviation() <= tolerance
+    }
+
+    pub fn max_block_deviation(&self) -> S {
+        self.block_deviation
+            .values
+            .data()
+            .iter()
+            .fold(S::zero(), |m, &v| if v > m { v } else { m })
+    }
+
+    /// Micro states sorted by decreasing row deviation.
+    pub fn worst_states(&self) -> Vec<(X, S)> {
+        let mut states: Vec<(X, S)> = self.state_deviation.enumerate().collect();
+        states.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+        states
+    }
+}
+
+impl<X, S> Markov<X, X, S>
+where
+    X: Ord + Clone,
+    S: Scalar,
+{
+    /// Compare the block-transition rows PF with FΦ, where Φ is the macro
+    /// kernel induced by `reference`. Micro states without a row in
+    /// `observable` are ignored.
+    pub fn lumpability<Y>(
+        &self,
+        observable: &Markov<X, Y, S>,
+        reference: &Prob<X, S>,
+    ) -> Result<Lumpability<X, Y, S>, BuildError>
+    where
+        Y: Ord + Clone,
+    {
+        let x_map = &self.matrix.x_ix_map;
+        let zero = S::zero();
+
+        // F with rows aligned to the micro kernel, PF with column labels of
+        // P matched to rows of F, and μ over the micro states
+        let f = observable.matrix.reindex_rows(x_map);
+        let pf = self.matrix.dot(&f);
+        let mu = reference.vector.reindex(x_map, zero);
+
+        // Φ = D⁻¹ Fᵀ diag(μ) PF with D = diag(Fᵀμ)
+        let mass = mu.dot(&f);
+        let phi = f
+            .map_rows(&mu, |v, m| v * m)
+            .transpose()
+            .dot(&pf)
+            .map_rows(&mass, |v, m| if m > zero { v / m } else { v });
+        let f_phi = f.dot(&phi);
+
+        // Rows are read as sparse vectors, so the deviations cost O(nnz)
+        let f_rows = f.values.to_csr();
+        let pf_rows = pf.values.to_csr();
+        let phi_rows = phi.values.to_csr();
+        let f_phi_rows = f_phi.values.to_csr();
+
+        let mut state_deviation = vec![zero; x_map.len()];
+        let mut block_deviation: BTreeMap<(usize, usize), S> = BTreeMap::new();
+        for (i, state) in state_deviation.iter_mut().enumerate() {
+            let pf_row = pf_rows.outer_view(i).expect("row of PF");
+            let f_phi_row = f_phi_rows.outer_view(i).expect("row of FΦ");
+            *state = differences(&pf_row, &f_phi_row).fold(zero, |acc, (_, d)| acc + d);
+
+            let f_row = f_rows.outer_view(i).expect("row of F");
+            for (k, _) in f_row.iter().filter(|(_, &v)| v > zero) {
+                let phi_row = phi_rows.outer_view(k).expect("row of Φ");
+                for (l, deviation) in differences(&pf_row, &phi_row) {
+                    let entry = block_deviation.entry((k, l)).or_insert(zero);
+                    if deviation > *entry {
+                        *entry = deviation;
+                    }
+                }
+            }
+        }
+
+        let intertwining_error = mu
+            .values
+            .iter()
+            .zip(&state_deviation)
+            .fold(zero, |acc, (&a, &b)| acc + a * b);
+
+        let y_map = &phi.x_ix_map;
+        let label_y = |k: usize| y_map.value_of(k).cloned();
+        Ok(Lumpability {
+            induced: Markov::from_matrix(positive_entries(&phi))?,
+            block_rows: positive_entries(&pf),
+            block_deviation: Matrix::from_entries(
+                block_deviation
+                    .into_iter()
+                    .filter_map(|((k, l), v)| Some((label_y(k)?, label_y(l)?, v))),
+            ),
+            state_deviation: Vector {
+                values: state_deviation.into(),
+                ix_map: x_map.clone(),
+            },
+            intertwining_error,
+        })
+    }
+
+    /// Lumpability of the time-reversed chain. If it is strongly lumpable,
+    /// the lumped process started from `stationary` is Markov (weak
+    /// lumpability), even when the forward chain is not strongly lumpable.
+    pub fn reverse_lumpability<Y>(
+        &self,
+        observable: &Markov<X, Y, S>,
+        stationary: &Prob<X, S>,
+    ) -> Result<Lumpability<X, Y, S>, BuildError>
+    where
+        Y: Ord + Clone,
+    {
+        self.time_reversal(stationary)?
+            .lumpability(observable, stationary)
+    }
+}
+
+fn positive_entries<A, B, S>(matrix: &Matrix<A, B, S>) -> Matrix<A, B, S>
+where
+    A: Ord + Clone,
+    B: Ord + Clone,
+    S: Scalar,
+{
+    Matrix::from_entries(
+        matrix
+            .values
+            .iter()
+            .filter(|(v, _)| **v > S::zero())
+            .filter_map(|(&v, (i, j))| {
+                let x = matrix.x_ix_map.value_of(i)?;
+                let y = matrix.y_ix_map.value_of(j)?;
+                Some((x.clone(), y.clone(), v))
+            }),
+    )
+}
+
+// |a_l − b_l| over the union of the supports of two sparse rows
+fn differences<'a, S: Scalar>(
+    a: &'a CsVecView<'a, S>,
+    b: &'a CsVecView<'a, S>,
+) -> impl Iterator<Item = (usize, S)> + 'a {
+    a.iter().nnz_or_zip(b.iter()).map(|entry| match entry {
+        NnzEither::Both((l, &a, &b)) => (l, (a - b).abs()),
+        NnzEither::Left((l, &a)) => (l, a.abs()),
+        NnzEither::Right((l, &b)) => (l, b.abs()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn partition() -> Markov<&'static str, u8> {
+        Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", 0, 1.0),
+            ("b", 0, 1.0),
+            ("c", 1, 1.0),
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_strongly_lumpable_partition() {
+        // a and b both move to {a, b} with probability 1/2
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "a", 1.0),
+            ("a", "c", 1.0),
+            ("b", "a", 1.0),
+            ("b", "b", 1.0),
+            ("b", "c", 2.0),
+            ("c", "b", 1.0),
+        ]))
+        .unwrap();
+        let pi = markov.stationary_direct(1e-12).unwrap().distribution;
+
+        let report = markov.lumpability(&partition(), &pi).unwrap();
+        assert!(report.is_strongly_lumpable(1e-12));
+        assert!(report.intertwining_error < 1e-12);
+        assert!((report.induced.matrix.get(&0, &1).unwrap() - 0.5).abs() < 1e-12);
+        assert_eq!(report.induced.matrix.get(&1, &0), Some(1.0));
+    }
+
+    #[test]
+    fn test_deviation_points_at_offending_state() {
+        // a stays in block 0 but b leaves it
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "b", 1.0),
+            ("b", "c", 1.0),
+            ("c", "a", 1.0),
+        ]))
+        .unwrap();
+        let pi = Prob::from_vector(Vector::from_assoc(vec![("a", 1.0), ("b", 1.0), ("c", 1.0)]))
+            .unwrap();
+
+        let report = markov.lumpability(&partition(), &pi).unwrap();
+        assert!(!report.is_strongly_lumpable(1e-9));
+        assert!((report.block_deviation.get(&0, &1).unwrap() - 0.5).abs() < 1e-12);
+        // Both a and b deviate by 1 in L1; c is alone in its block
+        assert!((report.intertwining_error - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(report.worst_states().last(), Some(&("c", 0.0)));
+    }
+
+    #[test]
+    fn test_exact_lumpability_with_rationals() {
+        use num_rational::Rational64;
+        let r = |n, d| Rational64::new(n, d);
+
+        // Thirds are not representable in binary, but exact here
+        let markov = Markov::from_matrix(Matrix::from_entries(vec![
+            ("a", "a", r(1, 3)),
+            ("a", "b", r(1, 3)),
+            ("a", "c", r(1, 3)),
+            ("b", "b", r(2, 3)),
+            ("b", "c", r(1, 3)),
+            ("c", "a", r(1, 1)),
+        ]))
+        .unwrap();
+        let pi = Prob::from_vector(Vector::from_entries(vec![
+            ("a", r(1, 1)),
+            ("b", r(1, 1)),
+            ("c", r(1, 1)),
+        ]))
+        .unwrap();
+        let partition = Markov::from_matrix(Matrix::from_entries(vec![
+            ("a", 0, r(1, 1)),
+            ("b", 0, r(1, 1)),
+            ("c", 1, r(1, 1)),
+        ]))
+        .unwrap();
+
+        let report = markov.lumpability(&partition, &pi).unwrap();
+        assert!(report.is_strongly_lumpable(r(0, 1)));
+        assert_eq!(report.intertwining_error, r(0, 1));
+        assert_eq!(report.induced.matrix.get(&0, &1), Some(r(1, 3)));
+    }
+}
@@ -1,21 +1,29 @@
 use ndarray::linalg::Dot;
+use sprs::TriMat;
+use std::sync::Arc;
 
+use crate::ix_map::IxMap;
 use crate::matrix::Matrix;
 use crate::prob::Prob;
-use crate::vector::max_difference;
+use crate::scalar::Scalar;
+
+/// Observable values with less mass than this get no row in the induced
+/// kernel.
+const MASS_TOLERANCE: f64 = 1e-10;
 
 /// Row-stochastic Markov kernel
 #[derive(Debug, Clone)]
-pub struct Markov<X, Y> {
-    pub matrix: Matrix<X, Y>,
+pub struct Markov<X, Y, S = f64> {
+    pub matrix: Matrix<X, Y, S>,
 }
 
-impl<X, Y> Markov<X, Y>
+impl<X, Y, S> Markov<X, Y, S>
 where
     X: Ord + Clone,
     Y: Ord + Clone,
+    S: Scalar,
 {
-    pub fn from_matrix(matrix: Matrix<X, Y>) -> Result<Self, BuildError> {
+    pub fn from_matrix(matrix: Matrix<X, Y, S>) -> Result<Self, BuildError> {
         let nrows = matrix.x_ix_map.len();
         let ncols = matrix.y_ix_map.len();
 
@@ -23,13 +31,13 @@ where
             return Err(BuildError::EmptyMatrix);
         }
 
-        if matrix.values.data().iter().any(|s| *s < 0.0) {
+        if matrix.values.data().iter().any(|s| *s < S::zero()) {
             return Err(BuildError::NegativeValue);
         }
 
         let row_sums = matrix.get_rows_sums();
 
-        if row_sums.values.iter().any(|s| *s <= 0.0) {
+        if row_sums.values.iter().any(|s| *s <= S::zero()) {
             return Err(BuildError::EmptyRow);
         }
 
@@ -39,12 +47,56 @@ where
     }
 
     /// To matrix
-    pub fn to_matrix(&self) -> &Matrix<X, Y> {
+    pub fn to_matrix(&self) -> &Matrix<X, Y, S> {
         &self.matrix
     }
 
+    /// Compose two kernels: (PQ)_xz = Σ_y P_xy Q_yz.
+    /// Every column label of `self` must be a row label of `other`.
+    pub fn compose<Z>(&self, other: &Markov<Y, Z, S>) -> Result<Markov<X, Z, S>, BuildError>
+    where
+        Z: Ord + Clone,
+    {
+        let rows = &other.matrix.x_ix_map;
+        if self
+            .matrix
+            .y_ix_map
+            .iter()
+            .any(|(_, y)| rows.index_of(y).is_none())
+        {
+            return Err(BuildError::LabelMismatch);
+        }
+
+        Ok(Markov {
+            matrix: self.matrix.dot(&other.matrix),
+        })
+    }
+
+    /// Bayesian inverse P(x | y) = p_x P_xy / (pP)_y under the prior `p`.
+    /// Column labels the prior cannot reach get no row.
+    pub fn bayesian_inverse(&self, prior: &Prob<X, S>) -> Result<Markov<Y, X, S>, BuildError> {
+        let prior = prior.vector.reindex(&self.matrix.x_ix_map, S::zero());
+        let joint = self.matrix.map_rows(&prior, |v, p| v * p).transpose();
+
+        let mass = joint.get_rows_sums();
+        let rows = IxMap::from_distinct_sorted(
+            mass.enumerate()
+                .filter(|(_, m)| *m > S::zero())
+                .map(|(y, _)| y),
+        );
+        Markov::from_matrix(joint.reindex_rows(&Arc::new(rows)))
+    }
+
+    /// Posterior P(x | y) over the row labels for a single observation `y`,
+    /// or `None` if `y` is unknown or has zero probability under the prior.
+    pub fn posterior(&self, prior: &Prob<X, S>, y: &Y) -> Option<Prob<X, S>> {
+        let likelihood = self.matrix.get_column(y)?;
+        let prior = prior.vector.reindex(&likelihood.ix_map, S::zero());
+        Prob::from_vector(&likelihood * &prior).ok()
+    }
+
     /// Enumerate all (row_label, col_label, value) triplets.
-    pub fn enumerate(&self) -> impl Iterator<Item = (X, Y, f64)> + '_ {
+    pub fn enumerate(&self) -> impl Iterator<Item = (X, Y, S)> + '_ {
         self.matrix
             .values
             .iter()
@@ -61,27 +113,61 @@ impl<X> Markov<X, X>
 where
     X: Ord + Clone,
 {
+    /// Rows and columns carry the same labels, so a column index is also
+    /// the row index of the same state.
+    pub(crate) fn has_square_labels(&self) -> bool {
+        self.matrix.x_ix_map == self.matrix.y_ix_map
+    }
+
+    /// Outgoing transitions of each state, indexed by row. Column labels
+    /// are mapped back to row indices; targets without a row are dropped,
+    /// see `leaking_rows`.
+    pub(crate) fn successors(&self) -> Vec<Vec<(usize, f64)>> {
+        let col_to_row = self.col_to_row();
+        let mut successors = vec![Vec::new(); self.matrix.x_ix_map.len()];
+        for (&val, (i, j)) in self.matrix.values.iter() {
+            if val <= 0.0 {
+                continue;
+            }
+            if let Some(target) = col_to_row[j] {
+                successors[i].push((target, val));
+            }
+        }
+        successors
+    }
+
+    /// Whether each row puts weight on a target without a row, the
+    /// transitions `successors` leaves out.
+    pub(crate) fn leaking_rows(&self) -> Vec<bool> {
+        let col_to_row = self.col_to_row();
+        let mut leaking = vec![false; self.matrix.x_ix_map.len()];
+        for (&val, (i, j)) in self.matrix.values.iter() {
+            if val > 0.0 && col_to_row[j].is_none() {
+                leaking[i] = true;
+            }
+        }
+        leaking
+    }
+
+    fn col_to_row(&self) -> Vec<Option<usize>> {
+        let x_map = &self.matrix.x_ix_map;
+        let y_map = &self.matrix.y_ix_map;
+        (0..y_map.len())
+            .map(|j| y_map.value_of(j).and_then(|y| x_map.index_of(y)))
+            .collect()
+    }
+
     /// Compute equilibrium distribution using power iteration.
+    /// Returns the last iterate even when `max_iterations` is reached; use
+    /// `stationary_power` or `stationary_direct` to inspect convergence.
     pub fn compute_equilibrium(
         &self,
         initial: &Prob<X>,
         tolerance: f64,
         max_iterations: usize,
     ) -> Prob<X> {
-        let mut current = initial.clone();
-
-        for _ in 0..max_iterations {
-            let next = current.dot(self);
-
-            let diff = max_difference(&current.vector, &next.vector);
-            if diff < tolerance {
-                return next;
-            }
-
-            current = next;
-        }
-
-        current
+        self.stationary_power(initial, tolerance, max_iterations)
+            .distribution
     }
 
     /// Compute the entropy rate of the Markov chain.
@@ -107,9 +193,110 @@ where
         -total
     }
 
+    /// Macro kernel Φ^f = D⁻¹ Fᵀ diag(p) Φ F seen through `observable` F
+    /// when the chain Φ is distributed as `p`, with D = diag(Fᵀp).
+    /// Observable values without mass under `p` get no row.
+    pub fn induced_kernel<Y>(
+        &self,
+        observable: &Markov<X, Y>,
+        p: &Prob<X>,
+    ) -> Result<Markov<Y, Y>, BuildError>
+    where
+        Y: Ord + Clone,
+    {
+        let x_map = &self.matrix.x_ix_map;
+        let f = observable.matrix.reindex_rows(x_map);
+        let p = p.vector.reindex(x_map, 0.0);
+
+        // D and Fᵀ diag(p) Φ F
+        let mass = p.dot(&f);
+        let joint = f
+            .map_rows(&p, |v, p| v * p)
+            .transpose()
+            .dot(&self.matrix)
+            .dot(&f);
+
+        let rows = IxMap::from_distinct_sorted(
+            mass.enumerate()
+                .filter(|(_, m)| m.abs() >= MASS_TOLERANCE)
+                .map(|(y, _)| y),
+        );
+        let kernel = joint
+            .map_rows(&mass, |v, m| v / m)
+            .reindex_rows(&Arc::new(rows));
+        Markov::from_matrix(kernel)
+    }
+}
+
+impl<X, S> Markov<X, X, S>
+where
+    X: Ord + Clone,
+    S: Scalar,
+{
+    /// n-step kernel Pⁿ, by repeated squaring.
+    pub fn power(&self, n: u32) -> Result<Self, BuildError> {
+        self.power_pruned(n, S::zero())
+    }
+
+    /// n-step kernel Pⁿ, dropping entries below `threshold` after each
+    /// product and renormalizing rows. Keeps powers of large chains sparse.
+    /// The largest entry of each row is always kept, so no row is emptied.
+    pub fn power_pruned(&self, n: u32, threshold: S) -> Result<Self, BuildError> {
+        let prune = |markov: Self| -> Result<Self, BuildError> {
+            if threshold <= S::zero() {
+                return Ok(markov);
+            }
+            let values = &markov.matrix.values;
+            let mut row_max = vec![S::zero(); values.rows()];
+            for (&v, (i, _)) in values.iter() {
+                if v > row_max[i] {
+                    row_max[i] = v;
+                }
+            }
+            let mut trimat = TriMat::new(values.shape());
+            for (&v, (i, j)) in values.iter() {
+                if v >= threshold || v == row_max[i] {
+                    trimat.add_triplet(i, j, v);
+                }
+            }
+            Markov::from_matrix(Matrix {
+                values: trimat.to_csc(),
+                x_ix_map: markov.matrix.x_ix_map.clone(),
+                y_ix_map: markov.matrix.y_ix_map.clone(),
+            })
+        };
+
+        let mut result: Option<Self> = None;
+        let mut base = self.clone();
+        let mut n = n;
+
+        while n > 0 {
+            if n & 1 == 1 {
+                result = Some(match result {
+                    Some(r) => prune(r.compose(&base)?)?,
+                    None => base.clone(),
+                });
+            }
+            n >>= 1;
+            if n > 0 {
+                base = prune(base.compose(&base)?)?;
+            }
+        }
+
+        match result {
+            Some(r) => Ok(r),
+            None => Markov::from_matrix(Matrix::from_entries(
+                self.matrix
+                    .x_ix_map
+                    .iter()
+                    .map(|(_, x)| (x.clone(), x.clone(), S::one())),
+            )),
+        }
+    }
+
     /// Compute the detailed balance deviation.
     /// Φ = (1/2) Σ_ij |π_i P_ij - π_j P_ji|
-    pub fn detailed_balance_deviation(&self, stationary: &Prob<X>) -> Matrix<X, X> {
+    pub fn detailed_balance_deviation(&self, stationary: &Prob<X, S>) -> Matrix<X, X, S> {
         let transition = self.matrix.map_rows(&stationary.vector, |v, p| v * p);
 
         let transpose = transition.transpose();
@@ -117,20 +304,57 @@ where
         transition.binop(&transpose, |x, y| x - y)
     }
 
-    pub fn detailed_balance_deviation_sum(&self, stationary: &Prob<X>) -> f64 {
+    /// Time-reversed kernel P*_ij = π_j P_ji / π_i.
+    /// Fails with `BuildError::EmptyRow` if some state has zero stationary
+    /// probability, since its reversed transitions are undefined.
+    pub fn time_reversal(&self, stationary: &Prob<X, S>) -> Result<Self, BuildError> {
+        let flow = self.matrix.map_rows(&stationary.vector, |v, p| v * p);
+        let reversed = flow.transpose().map_rows(&stationary.vector, |v, p| {
+            if p > S::zero() {
+                v / p
+            } else {
+                S::zero()
+            }
+        });
+        Markov::from_matrix(reversed)
+    }
+
+    /// Additive reversibilization (P + P*) / 2.
+    pub fn additive_reversibilization(&self, stationary: &Prob<X, S>) -> Result<Self, BuildError> {
+        let reversed = self.time_reversal(stationary)?;
+        Markov::from_matrix(
+            self.matrix
+                .binop(&reversed.matrix, |x, y| (x + y) / (S::one() + S::one())),
+        )
+    }
+
+    /// Multiplicative reversibilization P P*.
+    pub fn multiplicative_reversibilization(
+        &self,
+        stationary: &Prob<X, S>,
+    ) -> Result<Self, BuildError> {
+        self.compose(&self.time_reversal(stationary)?)
+    }
+
+    pub fn detailed_balance_deviation_sum(&self, stationary: &Prob<X, S>) -> S {
         let matrix = self.detailed_balance_deviation(stationary);
-        matrix.values.iter().map(|(v, _)| v.abs() / 2.0).sum()
+        let total = matrix
+            .values
+            .iter()
+            .fold(S::zero(), |acc, (v, _)| acc + v.abs());
+        total / (S::one() + S::one())
     }
 }
 
 // Implement Dot<Markov> for Prob: vector · matrix -> vector
-impl<X, Y> Dot<Markov<X, Y>> for Prob<X>
+impl<X, Y, S> Dot<Markov<X, Y, S>> for Prob<X, S>
 where
-    X: Ord,
+    X: Ord + Clone,
     Y: Ord + Clone,
+    S: Scalar,
 {
-    type Output = Prob<Y>;
-    fn dot(&self, markov: &Markov<X, Y>) -> Prob<Y> {
+    type Output = Prob<Y, S>;
+    fn dot(&self, markov: &Markov<X, Y, S>) -> Prob<Y, S> {
         Prob::from_vector(self.vector.dot(&markov.matrix)).unwrap()
     }
 }
@@ -143,6 +367,8 @@ pub enum BuildError {
     EmptyRow,
     #[error("matrix has zero size")]
     EmptyMatrix,
+    #[error("kernel labels do not match")]
+    LabelMismatch,
 }
 
 #[cfg(test)]
@@ -150,6 +376,14 @@ mod tests {
     use super::*;
     use crate::Vector;
 
+    #[test]
+    fn test_kernels_are_thread_safe() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Markov<String, usize>>();
+        assert_send_sync::<Prob<String>>();
+        assert_send_sync::<Vector<String>>();
+    }
+
     #[test]
     fn test_prob_dot_markov_alice_bob_chico() {
         // Setup probability vector with one order: chico, alice, bob
@@ -190,4 +424,236 @@ mod tests {
         );
         println!("  Result: 1={}, 2={}", p1, p2);
     }
+
+    #[test]
+    fn test_compose_aligns_labels() {
+        // P: {a, b} -> {x, y}; Q: {w, x, y} -> {1, 2}, with an extra row w
+        let p = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "x", 0.5),
+            ("a", "y", 0.5),
+            ("b", "y", 1.0),
+        ]))
+        .unwrap();
+        let q = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("w", 1, 1.0),
+            ("x", 1, 1.0),
+            ("y", 1, 0.2),
+            ("y", 2, 0.8),
+        ]))
+        .unwrap();
+
+        let pq = p.compose(&q).unwrap();
+
+        assert!((pq.matrix.get(&"a", &1).unwrap() - 0.6).abs() < 1e-12);
+        assert!((pq.matrix.get(&"a", &2).unwrap() - 0.4).abs() < 1e-12);
+        assert!((pq.matrix.get(&"b", &2).unwrap() - 0.8).abs() < 1e-12);
+
+        // z has no row in Q
+        let r = Markov::from_matrix(Matrix::from_assoc(vec![("a", "z", 1.0)])).unwrap();
+        assert!(matches!(r.compose(&q), Err(BuildError::LabelMismatch)));
+    }
+
+    #[test]
+    fn test_induced_kernel_matches_lumpability() {
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "b", 1.0),
+            ("b", "a", 1.0),
+            ("b", "c", 3.0),
+            ("c", "a", 2.0),
+            ("c", "c", 1.0),
+        ]))
+        .unwrap();
+        let observable = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", 0, 1.0),
+            ("b", 0, 1.0),
+            ("b", 1, 1.0),
+            ("c", 1, 1.0),
+        ]))
+        .unwrap();
+        let p = Prob::from_vector(Vector::from_assoc(vec![("a", 0.2), ("b", 0.5), ("c", 0.3)]))
+            .unwrap();
+
+        let kernel = markov.induced_kernel(&observable, &p).unwrap();
+        let induced = markov.lumpability(&observable, &p).unwrap().induced;
+        for (y, z) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+            let expected = induced.matrix.get(&y, &z).unwrap_or(0.0);
+            assert!((kernel.matrix.get(&y, &z).unwrap() - expected).abs() < 1e-12);
+        }
+
+        // Without mass on {b, c}, value 1 has no row
+        let p = Prob::from_vector(Vector::from_assoc(vec![("a", 1.0)])).unwrap();
+        let kernel = markov.induced_kernel(&observable, &p).unwrap();
+        assert_eq!(kernel.matrix.x_ix_map.len(), 1);
+        assert_eq!(kernel.matrix.get(&0, &1), Some(0.5));
+    }
+
+    #[test]
+    fn test_induced_kernel_matches_per_pair_formula() {
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "a", 0.1),
+            ("a", "b", 0.6),
+            ("a", "d", 0.3),
+            ("b", "c", 0.8),
+            ("b", "a", 0.2),
+            ("c", "a", 0.5),
+            ("c", "c", 0.25),
+            ("c", "d", 0.25),
+            ("d", "b", 1.0),
+        ]))
+        .unwrap();
+        let observable = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", 0, 0.5),
+            ("a", 1, 0.5),
+            ("b", 1, 1.0),
+            ("c", 1, 0.3),
+            ("c", 2, 0.7),
+            ("d", 3, 1.0),
+        ]))
+        .unwrap();
+        // Value 3 is only seen from d, which has no mass
+        let p = Prob::from_vector(Vector::from_assoc(vec![
+            ("a", 0.4),
+            ("b", 0.35),
+            ("c", 0.25),
+            ("d", 0.0),
+        ]))
+        .unwrap();
+
+        let kernel = markov.induced_kernel(&observable, &p).unwrap();
+
+        // Φ^f_yy' = (p ⊙ F_y) · Φ · F_y' / (p · F_y), one pair at a time
+        let p = p.to_vec();
+        let values: Vec<i32> = observable.matrix.y_ix_map.iter().map(|(_, &y)| y).collect();
+        for y in &values {
+            let f_y = observable.matrix.get_column(y).unwrap();
+            let denominator = p.dot(&f_y);
+            if denominator.abs() < 1e-10 {
+                assert!(kernel.matrix.x_ix_map.index_of(y).is_none());
+                continue;
+            }
+            let flow = (&p * &f_y).dot(&markov.matrix);
+            for y_prime in &values {
+                let f_y_prime = observable.matrix.get_column(y_prime).unwrap();
+                let expected = flow.dot(&f_y_prime) / denominator;
+                let actual = kernel.matrix.get(y, y_prime).unwrap_or(0.0);
+                assert!((actual - expected).abs() < 1e-12, "({y}, {y_prime})");
+            }
+        }
+        assert_eq!(kernel.matrix.x_ix_map.len(), 3);
+    }
+
+    #[test]
+    fn test_scalar_types() {
+        use num_rational::Rational64;
+        let r = |n, d| Rational64::new(n, d);
+
+        // Birth-death chains are reversible, so the deviation is exactly zero
+        let markov = Markov::from_matrix(Matrix::from_entries(vec![
+            ("a", "a", r(1, 2)),
+            ("a", "b", r(1, 2)),
+            ("b", "a", r(1, 3)),
+            ("b", "c", r(2, 3)),
+            ("c", "b", r(1, 1)),
+        ]))
+        .unwrap();
+        let pi = Prob::from_vector(Vector::from_entries(vec![
+            ("a", r(2, 1)),
+            ("b", r(3, 1)),
+            ("c", r(2, 1)),
+        ]))
+        .unwrap();
+        assert_eq!(markov.detailed_balance_deviation_sum(&pi), r(0, 1));
+        assert_eq!(pi.dot(&markov).prob(&"b"), Some(r(3, 7)));
+
+        let single = Markov::from_matrix(Matrix::from_entries(vec![
+            (0, 0, 1.0_f32),
+            (0, 1, 3.0),
+            (1, 0, 1.0),
+        ]))
+        .unwrap();
+        assert_eq!(single.power(2).unwrap().matrix.get(&1, &1), Some(0.75_f32));
+    }
+
+    #[test]
+    fn test_bayesian_inverse() {
+        // Two microstates read as "on", one as "off"
+        let observable = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "on", 1.0),
+            ("b", "on", 1.0),
+            ("b", "off", 1.0),
+            ("c", "off", 1.0),
+        ]))
+        .unwrap();
+        let prior = Prob::from_vector(Vector::from_assoc(vec![("a", 0.5), ("b", 0.5)])).unwrap();
+
+        let inverse = observable.bayesian_inverse(&prior).unwrap();
+        // P(on) = 0.5 + 0.25, so P(a | on) = 2/3
+        assert!((inverse.matrix.get(&"on", &"a").unwrap() - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(inverse.matrix.get(&"off", &"b"), Some(1.0));
+
+        let on = observable.posterior(&prior, &"on").unwrap();
+        assert!((on.prob(&"b").unwrap() - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(on.prob(&"c"), Some(0.0));
+        assert!(observable.posterior(&prior, &"unknown").is_none());
+    }
+
+    #[test]
+    fn test_power_by_squaring() {
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "a", 0.5),
+            ("a", "b", 0.5),
+            ("b", "a", 1.0),
+        ]))
+        .unwrap();
+
+        let mut expected = markov.clone();
+        for _ in 1..5 {
+            expected = expected.compose(&markov).unwrap();
+        }
+        let power = markov.power(5).unwrap();
+        for (x, y, v) in expected.enumerate() {
+            assert!((power.matrix.get(&x, &y).unwrap() - v).abs() < 1e-12);
+        }
+
+        let identity = markov.power(0).unwrap();
+        assert_eq!(identity.matrix.get(&"a", &"a"), Some(1.0));
+        assert_eq!(identity.matrix.get(&"a", &"b"), Some(0.0));
+
+        // Pruning keeps rows stochastic
+        let pruned = markov.power_pruned(5, 0.4).unwrap();
+        let sums = pruned.matrix.get_rows_sums();
+        assert!(sums.values.iter().all(|s| (s - 1.0).abs() < 1e-12));
+
+        // A threshold above every entry leaves each row's largest one
+        let pruned = markov.power_pruned(5, 0.9).unwrap();
+        assert_eq!(pruned.matrix.values.nnz(), 2);
+        assert_eq!(pruned.matrix.get(&"a", &"a"), Some(1.0));
+    }
+
+    #[test]
+    fn test_time_reversal_of_cycle() {
+        // Biased walk on a three-cycle: the reversal walks the other way
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "b", 0.8),
+            ("a", "c", 0.2),
+            ("b", "c", 0.8),
+            ("b", "a", 0.2),
+            ("c", "a", 0.8),
+            ("c", "b", 0.2),
+        ]))
+        .unwrap();
+        let pi = markov.stationary_direct(1e-12).unwrap().distribution;
+
+        let reversed = markov.time_reversal(&pi).unwrap();
+        assert!((reversed.matrix.get(&"a", &"c").unwrap() - 0.8).abs() < 1e-12);
+        assert!((reversed.matrix.get(&"a", &"b").unwrap() - 0.2).abs() < 1e-12);
+
+        // Both reversibilizations satisfy detailed balance
+        let additive = markov.additive_reversibilization(&pi).unwrap();
+        assert!(additive.detailed_balance_deviation_sum(&pi) < 1e-12);
+        assert!((additive.matrix.get(&"a", &"b").unwrap() - 0.5).abs() < 1e-12);
+
+        let multiplicative = markov.multiplicative_reversibilization(&pi).unwrap();
+        assert!(multiplicative.detailed_balance_deviation_sum(&pi) < 1e-12);
+    }
 }
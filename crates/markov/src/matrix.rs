@@ -1,30 +1,36 @@
 use ndarray::{linalg::Dot, Array1};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use sprs::binop::csmat_binop;
 use sprs::{CsMat, TriMat};
 use std::collections::BTreeMap;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::ix_map::IxMap;
+use crate::scalar::Scalar;
 use crate::vector::Vector;
 
 /// Matrix in CSC storage
 #[derive(Debug, Clone)]
-pub struct Matrix<X, Y> {
+pub struct Matrix<X, Y, S = f64> {
     /// Stored as CSC for your requested layout.
-    pub values: CsMat<f64>,
+    pub values: CsMat<S>,
     /// Row labels (X) <-> row indices
-    pub x_ix_map: Rc<IxMap<X>>,
+    pub x_ix_map: Arc<IxMap<X>>,
     /// Column labels (Y) <-> column indices
-    pub y_ix_map: Rc<IxMap<Y>>,
+    pub y_ix_map: Arc<IxMap<Y>>,
 }
 
-impl<X, Y> Matrix<X, Y>
+impl<X, Y, S> Matrix<X, Y, S>
 where
     X: Ord + Clone,
     Y: Ord + Clone,
+    S: Scalar,
 {
-    pub fn from_assoc(assoc: impl IntoIterator<Item = (X, Y, f64)>) -> Self {
-        let mut x_map: BTreeMap<X, Vec<(Y, f64)>> = BTreeMap::new();
+    /// Build from (row, column, value) triplets, for any scalar type.
+    /// Repeated entries are added up.
+    pub fn from_entries(assoc: impl IntoIterator<Item = (X, Y, S)>) -> Self {
+        let mut x_map: BTreeMap<X, Vec<(Y, S)>> = BTreeMap::new();
 
         for (x, y, n) in assoc.into_iter() {
             x_map.entry(x).or_default().push((y, n));
@@ -32,15 +38,15 @@ where
 
         let x_size: usize = x_map.len();
         let mut x_keys: Vec<X> = Vec::new();
-        let mut x_values: Vec<(Y, usize, f64)> = Vec::new();
+        let mut x_values: Vec<(Y, usize, S)> = Vec::new();
 
         for (i, (x, v)) in x_map.into_iter().enumerate() {
             x_keys.push(x.clone());
-            let x_value: Vec<(Y, usize, f64)> = v.into_iter().map(|(y, n)| (y, i, n)).collect();
+            let x_value: Vec<(Y, usize, S)> = v.into_iter().map(|(y, n)| (y, i, n)).collect();
             x_values.extend(x_value);
         }
 
-        let mut y_map: BTreeMap<Y, Vec<(usize, f64)>> = BTreeMap::new();
+        let mut y_map: BTreeMap<Y, Vec<(usize, S)>> = BTreeMap::new();
 
         for (y, i, n) in x_values.into_iter() {
             y_map.entry(y).or_default().push((i, n));
@@ -48,11 +54,11 @@ where
 
         let y_size: usize = y_map.len();
         let mut y_keys: Vec<Y> = Vec::new();
-        let mut triples: Vec<(usize, usize, f64)> = Vec::new();
+        let mut triples: Vec<(usize, usize, S)> = Vec::new();
 
         for (j, (y, v)) in y_map.into_iter().enumerate() {
             y_keys.push(y.clone());
-            let triple: Vec<(usize, usize, f64)> = v.into_iter().map(|(i, n)| (i, j, n)).collect();
+            let triple: Vec<(usize, usize, S)> = v.into_iter().map(|(i, n)| (i, j, n)).collect();
             triples.extend(triple);
         }
 
@@ -62,27 +68,35 @@ where
             trimat.add_triplet(i, j, v);
         }
 
-        let values: CsMat<f64> = trimat.to_csc();
+        let values: CsMat<S> = trimat.to_csc();
 
         let x_ix_map = IxMap::from_distinct_sorted(x_keys);
         let y_ix_map = IxMap::from_distinct_sorted(y_keys);
 
         Self {
             values,
-            x_ix_map: Rc::new(x_ix_map),
-            y_ix_map: Rc::new(y_ix_map),
+            x_ix_map: Arc::new(x_ix_map),
+            y_ix_map: Arc::new(y_ix_map),
         }
     }
 
-    /// Get a column as a Vector<X>.
-    pub fn get_column(&self, col_index: &Y) -> Option<Vector<X>> {
+    /// Get the entry at (x, y) if both labels are known; missing entries
+    /// of the sparse storage are zero.
+    pub fn get(&self, x: &X, y: &Y) -> Option<S> {
+        let i = self.x_ix_map.index_of(x)?;
+        let j = self.y_ix_map.index_of(y)?;
+        Some(self.values.get(i, j).copied().unwrap_or(S::zero()))
+    }
+
+    /// Get a column as a Vector<X, S>.
+    pub fn get_column(&self, col_index: &Y) -> Option<Vector<X, S>> {
         let ix = self.y_ix_map.index_of(col_index)?;
         let vector = get_csmat_column(&self.values, &self.x_ix_map, ix);
         Some(vector)
     }
 
-    /// Get columns as a Vec of Vector<X>.
-    pub fn get_columns(&self) -> Vec<Vector<X>> {
+    /// Get columns as a Vec of Vector<X, S>.
+    pub fn get_columns(&self) -> Vec<Vector<X, S>> {
         let mut columns = Vec::new();
         for ix in 0..self.y_ix_map.len() {
             let vector = get_csmat_column(&self.values, &self.x_ix_map, ix);
@@ -91,29 +105,25 @@ where
         columns
     }
 
-    pub fn get_rows_sums(&self) -> Vector<X> {
-        let mut row_sums = Array1::zeros(self.x_ix_map.len());
-
-        for col in self.values.outer_iterator() {
-            for (&row, &val) in col.indices().iter().zip(col.data().iter()) {
-                row_sums[row] += val;
-            }
-        }
-
+    pub fn get_rows_sums(&self) -> Vector<X, S> {
         Vector {
             ix_map: self.x_ix_map.clone(),
-            values: row_sums,
+            values: row_reduce(&self.values, |_, val| val),
         }
     }
 
     // Applies (m_ij, v_i) -> f(m_ij, v_i)
-    pub fn map_rows<F: Fn(f64, f64) -> f64>(&self, vector: &Vector<X>, f: F) -> Matrix<X, Y> {
+    pub fn map_rows<F>(&self, vector: &Vector<X, S>, f: F) -> Matrix<X, Y, S>
+    where
+        F: Fn(S, S) -> S,
+    {
         let mut mat = self.values.clone();
-        for mut col in mat.outer_iterator_mut() {
-            for (row, val) in col.iter_mut() {
-                *val = f(*val, vector.values[row]);
-            }
+        let rows = mat.indices().to_vec();
+        let weights = &vector.values;
+        for (val, row) in mat.data_mut().iter_mut().zip(&rows) {
+            *val = f(*val, weights[*row]);
         }
+
         Matrix {
             values: mat,
             x_ix_map: self.x_ix_map.clone(),
@@ -121,7 +131,7 @@ where
         }
     }
 
-    pub fn transpose(&self) -> Matrix<Y, X> {
+    pub fn transpose(&self) -> Matrix<Y, X, S> {
         let transpose = self.values.view().transpose_into().to_csc();
         Matrix {
             x_ix_map: self.y_ix_map.clone(),
@@ -130,7 +140,55 @@ where
         }
     }
 
-    pub fn binop<F: Fn(f64, f64) -> f64>(&self, other: &Matrix<X, Y>, f: F) -> Matrix<X, Y> {
+    /// Reorder rows to follow `ix_map`. Labels missing from `self` give empty
+    /// rows, and rows whose label is not in `ix_map` are dropped.
+    pub fn reindex_rows(&self, ix_map: &Arc<IxMap<X>>) -> Matrix<X, Y, S> {
+        if Arc::ptr_eq(&self.x_ix_map, ix_map) || self.x_ix_map == *ix_map {
+            return Matrix {
+                values: self.values.clone(),
+                x_ix_map: ix_map.clone(),
+                y_ix_map: self.y_ix_map.clone(),
+            };
+        }
+
+        let mut trimat = TriMat::new((ix_map.len(), self.y_ix_map.len()));
+        for (&val, (i, j)) in self.values.iter() {
+            if let Some(k) = self.x_ix_map.value_of(i).and_then(|x| ix_map.index_of(x)) {
+                trimat.add_triplet(k, j, val);
+            }
+        }
+        Matrix {
+            values: trimat.to_csc(),
+            x_ix_map: ix_map.clone(),
+            y_ix_map: self.y_ix_map.clone(),
+        }
+    }
+
+    /// Reorder columns to follow `ix_map`. Labels missing from `self` give
+    /// empty columns, and columns whose label is not in `ix_map` are dropped.
+    pub fn reindex_cols(&self, ix_map: &Arc<IxMap<Y>>) -> Matrix<X, Y, S> {
+        if Arc::ptr_eq(&self.y_ix_map, ix_map) || self.y_ix_map == *ix_map {
+            return Matrix {
+                values: self.values.clone(),
+                x_ix_map: self.x_ix_map.clone(),
+                y_ix_map: ix_map.clone(),
+            };
+        }
+
+        let mut trimat = TriMat::new((self.x_ix_map.len(), ix_map.len()));
+        for (&val, (i, j)) in self.values.iter() {
+            if let Some(k) = self.y_ix_map.value_of(j).and_then(|y| ix_map.index_of(y)) {
+                trimat.add_triplet(i, k, val);
+            }
+        }
+        Matrix {
+            values: trimat.to_csc(),
+            x_ix_map: self.x_ix_map.clone(),
+            y_ix_map: ix_map.clone(),
+        }
+    }
+
+    pub fn binop<F: Fn(S, S) -> S>(&self, other: &Matrix<X, Y, S>, f: F) -> Matrix<X, Y, S> {
         Matrix {
             x_ix_map: self.x_ix_map.clone(),
             y_ix_map: self.y_ix_map.clone(),
@@ -139,8 +197,58 @@ where
     }
 }
 
-fn get_csmat_column<X>(matrix: &CsMat<f64>, ix_map: &Rc<IxMap<X>>, ix: usize) -> Vector<X>
+impl<X, Y> Matrix<X, Y>
+where
+    X: Ord + Clone,
+    Y: Ord + Clone,
+{
+    /// Build from (row, column, f64) triplets.
+    pub fn from_assoc(assoc: impl IntoIterator<Item = (X, Y, f64)>) -> Self {
+        Self::from_entries(assoc)
+    }
+}
+
+// r_i = Σ_j f(j, m_ij) over the stored entries of each row. Serial even
+// with the `parallel` feature: splitting CSC columns needs a dense
+// accumulator per task, and a CSR copy measured slower (see the README).
+fn row_reduce<S, F>(values: &CsMat<S>, f: F) -> Array1<S>
+where
+    S: Scalar,
+    F: Fn(usize, S) -> S,
+{
+    let mut result = Array1::zeros(values.rows());
+    for (j, col) in values.outer_iterator().enumerate() {
+        for (i, &val) in col.iter() {
+            result[i] += f(j, val);
+        }
+    }
+    result
+}
+
+// c_j = Σ_i f(i, m_ij) over the stored entries of each column, one column
+// per task with the `parallel` feature.
+fn column_reduce<S, F>(values: &CsMat<S>, f: F) -> Array1<S>
+where
+    S: Scalar,
+    F: Fn(usize, S) -> S + Sync,
+{
+    let column = |j: usize| -> S {
+        values.outer_view(j).map_or(S::zero(), |col| {
+            col.iter().fold(S::zero(), |acc, (i, &val)| acc + f(i, val))
+        })
+    };
+
+    #[cfg(feature = "parallel")]
+    let result: Vec<S> = (0..values.cols()).into_par_iter().map(column).collect();
+    #[cfg(not(feature = "parallel"))]
+    let result: Vec<S> = (0..values.cols()).map(column).collect();
+
+    result.into()
+}
+
+fn get_csmat_column<X, S>(matrix: &CsMat<S>, ix_map: &Arc<IxMap<X>>, ix: usize) -> Vector<X, S>
 where
+    S: Scalar,
     X: Ord + Clone,
 {
     let col_view = matrix.outer_view(ix).unwrap();
@@ -148,33 +256,57 @@ where
 }
 
 // Vector Dot Matrix
-impl<X, Y> Dot<Matrix<X, Y>> for Vector<X>
+impl<X, Y, S> Dot<Matrix<X, Y, S>> for Vector<X, S>
 where
+    S: Scalar,
     X: Ord,
     Y: Ord + Clone,
 {
-    type Output = Vector<Y>;
+    type Output = Vector<Y, S>;
 
-    fn dot(&self, matrix: &Matrix<X, Y>) -> Vector<Y> {
+    fn dot(&self, matrix: &Matrix<X, Y, S>) -> Vector<Y, S> {
+        let weights = &self.values;
         Vector {
-            values: matrix.values.transpose_view().dot(&self.values),
+            values: column_reduce(&matrix.values, |i, val| val * weights[i]),
             ix_map: matrix.y_ix_map.clone(),
         }
     }
 }
 
 /// Matrix dot Vector
-impl<X, Y> Dot<Vector<Y>> for Matrix<X, Y>
+impl<X, Y, S> Dot<Vector<Y, S>> for Matrix<X, Y, S>
 where
+    S: Scalar,
     X: Ord + Clone,
     Y: Ord,
 {
-    type Output = Vector<X>;
+    type Output = Vector<X, S>;
 
-    fn dot(&self, vector: &Vector<Y>) -> Vector<X> {
+    fn dot(&self, vector: &Vector<Y, S>) -> Vector<X, S> {
+        let weights = &vector.values;
         Vector {
-            values: self.values.dot(&vector.values),
+            values: row_reduce(&self.values, |j, val| val * weights[j]),
             ix_map: self.x_ix_map.clone(),
         }
     }
 }
+
+/// Matrix dot Matrix, matching the inner labels by value
+impl<X, Y, Z, S> Dot<Matrix<Y, Z, S>> for Matrix<X, Y, S>
+where
+    S: Scalar,
+    X: Ord + Clone,
+    Y: Ord + Clone,
+    Z: Ord + Clone,
+{
+    type Output = Matrix<X, Z, S>;
+
+    fn dot(&self, other: &Matrix<Y, Z, S>) -> Matrix<X, Z, S> {
+        let other = other.reindex_rows(&self.y_ix_map);
+        Matrix {
+            values: (&self.values * &other.values).to_csc(),
+            x_ix_map: self.x_ix_map.clone(),
+            y_ix_map: other.y_ix_map,
+        }
+    }
+}
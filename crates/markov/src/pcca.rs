@@ -0,0 +1,283 @@
+use nalgebra::DMatrix;
+
+use crate::markov::{BuildError, Markov};
+use crate::matrix::Matrix;
+use crate::prob::Prob;
+
+/// Basis vectors with a smaller π-weighted norm after projection are
+/// treated as linearly dependent.
+const BASIS_TOLERANCE: f64 = 1e-8;
+
+/// Memberships below this are dropped from the kernels.
+const MEMBERSHIP_TOLERANCE: f64 = 1e-12;
+
+/// Macrostates found by PCCA+, labelled 0..k in order of the first micro
+/// state assigned to them.
+#[derive(Debug, Clone)]
+pub struct Coarsening<X> {
+    /// Fuzzy memberships χ_xk: non-negative rows summing to one.
+    pub memberships: Markov<X, usize>,
+    /// Hard partition sending each state to its largest membership.
+    pub partition: Markov<X, usize>,
+}
+
+impl<X> Markov<X, X>
+where
+    X: Ord + Clone,
+{
+    /// Split the chain into `k` metastable macrostates with PCCA+.
+    ///
+    /// The leading eigenvectors are orthonormalized with respect to
+    /// `stationary`, the inner simplex algorithm picks k vertices among
+    /// the states, and the memberships are the barycentric coordinates
+    /// of every state in that simplex, made feasible without the
+    /// rotation optimization step.
+    pub fn pcca(&self, k: usize, stationary: &Prob<X>) -> Result<Coarsening<X>, CoarseningError> {
+        let x_map = &self.matrix.x_ix_map;
+        let n = x_map.len();
+        if k == 0 || k > n {
+            return Err(CoarseningError::InvalidCount);
+        }
+
+        let weights: Vec<f64> = x_map
+            .iter()
+            .map(|(_, x)| stationary.prob(x).unwrap_or(0.0))
+            .collect();
+        let basis = self.real_basis(k, stationary, &weights)?;
+        let vertices = inner_simplex(&basis);
+
+        let mut rotation = DMatrix::from_fn(k, k, |a, b| basis[(vertices[a], b)])
+            .try_inverse()
+            .ok_or(CoarseningError::Degenerate)?;
+        fill_rotation(&mut rotation, &basis)?;
+
+        let chi = (&basis * &rotation).map(|v| if v < MEMBERSHIP_TOLERANCE { 0.0 } else { v });
+
+        // Hard assignment, then relabel by first appearance
+        let assignment: Vec<usize> = chi
+            .row_iter()
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .max_by(|a, b| a.1.total_cmp(b.1))
+                    .map_or(0, |(c, _)| c)
+            })
+            .collect();
+        let mut relabel = vec![None; k];
+        let mut next = 0;
+        for c in assignment.iter().copied().chain(0..k) {
+            if relabel[c].is_none() {
+                relabel[c] = Some(next);
+                next += 1;
+            }
+        }
+        let label = |c: usize| relabel[c].expect("every column is relabelled");
+
+        let memberships = Markov::from_matrix(Matrix::from_assoc(
+            chi.row_iter().enumerate().flat_map(|(i, row)| {
+                let x = x_map.value_of(i);
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, v)| **v > 0.0)
+                    .filter_map(|(c, &v)| Some((x?.clone(), label(c), v)))
+                    .collect::<Vec<_>>()
+            }),
+        ))?;
+        let partition = Markov::from_matrix(Matrix::from_assoc(
+            assignment
+                .iter()
+                .enumerate()
+                .filter_map(|(i, &c)| Some((x_map.value_of(i)?.clone(), label(c), 1.0))),
+        ))?;
+
+        Ok(Coarsening {
+            memberships,
+            partition,
+        })
+    }
+
+    // n × k matrix whose first column is constant and whose columns are
+    // π-orthonormal, spanning the leading eigenvectors. Complex pairs
+    // contribute their real and imaginary parts.
+    fn real_basis(
+        &self,
+        k: usize,
+        stationary: &Prob<X>,
+        weights: &[f64],
+    ) -> Result<DMatrix<f64>, CoarseningError> {
+        let n = weights.len();
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return Err(CoarseningError::Degenerate);
+        }
+        let spectrum = self.spectrum((k + 1).min(n), stationary);
+
+        let candidates = spectrum
+            .eigenpairs
+            .iter()
+            .flat_map(|e| [&e.right.re, &e.right.im])
+            .map(|v| v.values.to_vec());
+
+        let inner = |a: &[f64], b: &[f64]| -> f64 {
+            weights
+                .iter()
+                .zip(a)
+                .zip(b)
+                .map(|((w, x), y)| w * x * y)
+                .sum()
+        };
+
+        let mut columns = vec![vec![1.0 / total.sqrt(); n]];
+        for mut candidate in candidates {
+            if columns.len() == k {
+                break;
+            }
+            for column in &columns {
+                let projection = inner(&candidate, column);
+                candidate
+                    .iter_mut()
+                    .zip(column)
+                    .for_each(|(c, b)| *c -= projection * b);
+            }
+            let norm = inner(&candidate, &candidate).sqrt();
+            if norm > BASIS_TOLERANCE {
+                candidate.iter_mut().for_each(|c| *c /= norm);
+                columns.push(candidate);
+            }
+        }
+
+        if columns.len() < k {
+            return Err(CoarseningError::Degenerate);
+        }
+        Ok(DMatrix::from_fn(n, k, |i, c| columns[c][i]))
+    }
+}
+
+// Inner simplex algorithm: greedily pick the k rows of the basis that are
+// farthest apart, as vertices of the simplex containing all states.
+fn inner_simplex(basis: &DMatrix<f64>) -> Vec<usize> {
+    let (n, k) = basis.shape();
+    let farthest = |rows: &DMatrix<f64>| {
+        (0..n)
+            .max_by(|&a, &b| rows.row(a).norm().total_cmp(&rows.row(b).norm()))
+            .unwrap_or(0)
+    };
+
+    let mut vertices = vec![farthest(basis)];
+    let first = basis.row(vertices[0]).clone_owned();
+    let mut rows = DMatrix::from_fn(n, k, |i, c| basis[(i, c)] - first[c]);
+
+    for _ in 1..k {
+        let last = *vertices.last().expect("at least one vertex");
+        let direction = rows.row(last).clone_owned();
+        let norm = direction.norm();
+        if norm > 0.0 {
+            let direction = direction / norm;
+            for i in 0..n {
+                let projection = rows.row(i).dot(&direction);
+                let mut row = rows.row_mut(i);
+                row -= &direction * projection;
+            }
+        }
+        vertices.push(farthest(&rows));
+    }
+    vertices
+}
+
+// Make the memberships XA non-negative with rows summing to one: the rows
+// of A after the first get zero sums, and the first row lifts every
+// column to a minimum of zero.
+fn fill_rotation(rotation: &mut DMatrix<f64>, basis: &DMatrix<f64>) -> Result<(), CoarseningError> {
+    let k = rotation.nrows();
+    if k == 1 {
+        // The constant vector alone already gives χ = 1
+        return Ok(());
+    }
+    for a in 1..k {
+        let rest: f64 = (1..k).map(|b| rotation[(a, b)]).sum();
+        rotation[(a, 0)] = -rest;
+    }
+
+    let tail = basis.columns(1, k - 1) * rotation.rows(1, k - 1);
+    for c in 0..k {
+        let minimum = tail.column(c).min();
+        rotation[(0, c)] = -minimum;
+    }
+
+    let total: f64 = rotation.row(0).sum();
+    if total <= 0.0 || !total.is_finite() {
+        return Err(CoarseningError::Degenerate);
+    }
+    *rotation /= total;
+    Ok(())
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CoarseningError {
+    #[error("number of macrostates must be between 1 and the number of states")]
+    InvalidCount,
+    #[error("leading eigenvectors do not span enough macrostates")]
+    Degenerate,
+    #[error(transparent)]
+    Build(#[from] BuildError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two pairs of states that rarely cross between each other
+    fn two_wells() -> Markov<&'static str, &'static str> {
+        Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "a", 50.0),
+            ("a", "b", 50.0),
+            ("b", "a", 50.0),
+            ("b", "b", 49.0),
+            ("b", "c", 1.0),
+            ("c", "b", 1.0),
+            ("c", "c", 49.0),
+            ("c", "d", 50.0),
+            ("d", "c", 50.0),
+            ("d", "d", 50.0),
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_pcca_separates_metastable_wells() {
+        let markov = two_wells();
+        let pi = markov.stationary_direct(1e-12).unwrap().distribution;
+
+        let coarsening = markov.pcca(2, &pi).unwrap();
+        let partition = &coarsening.partition;
+        for (x, block) in [("a", 0), ("b", 0), ("c", 1), ("d", 1)] {
+            assert_eq!(partition.matrix.get(&x, &block), Some(1.0));
+        }
+
+        let memberships = &coarsening.memberships;
+        for x in ["a", "b", "c", "d"] {
+            let row: f64 = (0..2).filter_map(|k| memberships.matrix.get(&x, &k)).sum();
+            assert!((row - 1.0).abs() < 1e-9);
+        }
+        // The vertices of the simplex are fully assigned
+        assert!((memberships.matrix.get(&"a", &0).unwrap() - 1.0).abs() < 1e-9);
+        assert!(memberships.matrix.get(&"b", &0).unwrap() > 0.9);
+    }
+
+    #[test]
+    fn test_pcca_macrostate_count() {
+        let markov = two_wells();
+        let pi = markov.stationary_direct(1e-12).unwrap().distribution;
+
+        assert!(matches!(
+            markov.pcca(0, &pi),
+            Err(CoarseningError::InvalidCount)
+        ));
+        assert!(matches!(
+            markov.pcca(5, &pi),
+            Err(CoarseningError::InvalidCount)
+        ));
+        let single = markov.pcca(1, &pi).unwrap();
+        assert_eq!(single.memberships.matrix.get(&"c", &0), Some(1.0));
+    }
+}
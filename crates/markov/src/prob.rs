@@ -1,29 +1,31 @@
 use ndarray::linalg::Dot;
 
+use crate::scalar::Scalar;
 use crate::vector::Vector;
 
 /// Probability vector with a bidirectional map for labels X.
 #[derive(Debug, Clone)]
-pub struct Prob<X> {
-    pub vector: Vector<X>,
+pub struct Prob<X, S = f64> {
+    pub vector: Vector<X, S>,
 }
 
-impl<X> Prob<X>
+impl<X, S> Prob<X, S>
 where
     X: Ord + Clone,
+    S: Scalar,
 {
-    pub fn from_vector(vector: Vector<X>) -> Result<Self, BuildError> {
+    pub fn from_vector(vector: Vector<X, S>) -> Result<Self, BuildError> {
         if vector.is_empty() {
             return Err(BuildError::Empty);
         }
 
-        let has_negative = vector.values().any(|x| *x < 0.0);
+        let has_negative = vector.values().any(|x| *x < S::zero());
         if has_negative {
             return Err(BuildError::NegativeValue);
         }
 
-        let sum: f64 = vector.values().copied().sum();
-        if sum == 0.0 {
+        let sum = vector.values().fold(S::zero(), |acc, x| acc + *x);
+        if sum == S::zero() {
             return Err(BuildError::ZeroSum);
         }
 
@@ -34,25 +36,30 @@ where
     }
 
     /// To vector
-    pub fn to_vector(&self) -> &Vector<X> {
+    pub fn to_vector(&self) -> &Vector<X, S> {
         &self.vector
     }
 
     /// Get P[X = x] if `x` is known; otherwise None.
-    pub fn prob(&self, x: &X) -> Option<f64> {
+    pub fn prob(&self, x: &X) -> Option<S> {
         self.vector.get(x)
     }
 
     /// Convert to a Vector.
-    pub fn to_vec(&self) -> crate::vector::Vector<X> {
+    pub fn to_vec(&self) -> crate::vector::Vector<X, S> {
         self.vector.clone()
     }
 
     /// Enumerate all (label, probability) pairs.
-    pub fn enumerate(&self) -> impl Iterator<Item = (X, f64)> + '_ {
+    pub fn enumerate(&self) -> impl Iterator<Item = (X, S)> + '_ {
         self.vector.enumerate()
     }
+}
 
+impl<X> Prob<X>
+where
+    X: Ord + Clone,
+{
     /// Compute Shannon entropy using natural logarithm.
     pub fn entropy(&self) -> f64 {
         self.vector
@@ -68,12 +75,13 @@ where
 }
 
 // Implement Dot<Prob> for Prob: vector · vector -> scalar
-impl<X> Dot<Prob<X>> for Prob<X>
+impl<X, S> Dot<Prob<X, S>> for Prob<X, S>
 where
     X: Ord + Clone + std::fmt::Debug,
+    S: Scalar,
 {
-    type Output = f64;
-    fn dot(&self, rhs: &Prob<X>) -> f64 {
+    type Output = S;
+    fn dot(&self, rhs: &Prob<X, S>) -> S {
         self.vector.dot(&rhs.vector)
     }
 }
@@ -0,0 +1,277 @@
+use ndarray::Array1;
+use sprs::{CsMat, TriMat};
+use std::sync::Arc;
+
+use crate::linalg::{self, SolveError, SparseLu};
+use crate::markov::Markov;
+use crate::matrix::Matrix;
+use crate::prob::Prob;
+use crate::vector::Vector;
+
+/// Tolerance for the stationary distribution used by `average`.
+const STATIONARY_TOLERANCE: f64 = 1e-10;
+
+/// A chain together with the expected reward collected on each step.
+///
+/// State rewards R_x and transition rewards R_xy are folded into the
+/// expected one-step reward r_x = R_x + Σ_y P_xy R_xy, which is all the
+/// value computations depend on.
+#[derive(Debug, Clone)]
+pub struct RewardProcess<X> {
+    pub markov: Markov<X, X>,
+    /// Expected one-step reward r, over the rows of the kernel.
+    pub reward: Vector<X>,
+}
+
+/// Long-run average reward of a unichain, a chain with a single closed
+/// class.
+#[derive(Debug, Clone)]
+pub struct AverageReward<X> {
+    /// Gain g = π·r, the reward per step in the long run.
+    pub gain: f64,
+    /// Bias h, the solution of (I − P)h = r − g with π·h = 0: the excess
+    /// cumulative reward of starting from each state.
+    pub bias: Vector<X>,
+    pub stationary: Prob<X>,
+}
+
+impl<X> Markov<X, X>
+where
+    X: Ord + Clone,
+{
+    /// Attach state and/or transition rewards. Rewards are aligned by label;
+    /// states or transitions without an entry earn nothing.
+    pub fn with_rewards(
+        &self,
+        state: Option<&Vector<X>>,
+        transition: Option<&Matrix<X, X>>,
+    ) -> RewardProcess<X> {
+        let x_map = &self.matrix.x_ix_map;
+
+        let mut reward = match state {
+            Some(state) => state.reindex(x_map, 0.0).values,
+            None => Array1::zeros(x_map.len()),
+        };
+        if let Some(transition) = transition {
+            let transition = transition
+                .reindex_rows(x_map)
+                .reindex_cols(&self.matrix.y_ix_map);
+            for (&p, (i, j)) in self.matrix.values.iter() {
+                if let Some(r) = transition.values.get(i, j) {
+                    reward[i] += p * r;
+                }
+            }
+        }
+
+        RewardProcess {
+            markov: self.clone(),
+            reward: Vector {
+                values: reward,
+                ix_map: Arc::clone(x_map),
+            },
+        }
+    }
+}
+
+impl<X> RewardProcess<X>
+where
+    X: Ord + Clone,
+{
+    /// Expected reward collected over the first `horizon` steps from each
+    /// state, v_n = r + P v_(n−1) with v_0 = 0. Targets without a row
+    /// contribute nothing after they are reached.
+    pub fn cumulative(&self, horizon: usize) -> Vector<X> {
+        let successors = self.markov.successors();
+        let r = &self.reward.values;
+
+        let mut value = Array1::zeros(r.len());
+        for _ in 0..horizon {
+            value = Array1::from_iter(
+                successors
+                    .iter()
+                    .enumerate()
+                    .map(|(i, succ)| r[i] + succ.iter().map(|&(j, p)| p * value[j]).sum::<f64>()),
+            );
+        }
+        self.with_values(value)
+    }
+
+    /// Discounted value function, the solution of (I − γP)v = r. Requires
+    /// 0 ≤ γ < 1.
+    pub fn discounted(&self, gamma: f64) -> Result<Vector<X>, RewardError> {
+        if !(0.0..1.0).contains(&gamma) {
+            return Err(RewardError::InvalidDiscount(gamma));
+        }
+
+        let n = self.reward.len();
+        let mut tri = TriMat::new((n, n));
+        for i in 0..n {
+            tri.add_triplet(i, i, 1.0);
+        }
+        for (i, succ) in self.markov.successors().into_iter().enumerate() {
+            for (j, p) in succ {
+                tri.add_triplet(i, j, -gamma * p);
+            }
+        }
+        let system: CsMat<f64> = tri.to_csr();
+
+        let value = linalg::solve(&system, &self.reward.values)?;
+        Ok(self.with_values(value))
+    }
+
+    /// Gain and bias of the long-run average reward. Requires a single
+    /// closed class, which may be periodic; transient states are allowed.
+    pub fn average(&self) -> Result<AverageReward<X>, RewardError> {
+        let n = self.reward.len();
+        if !self.markov.has_square_labels() {
+            return Err(SolveError::NotSquare.into());
+        }
+        if self.markov.communicating_classes().closed_classes().count() != 1 {
+            return Err(RewardError::Multichain);
+        }
+
+        let stationary = self
+            .markov
+            .stationary_direct(STATIONARY_TOLERANCE)?
+            .distribution;
+        let pi = &stationary.vector.values;
+
+        // [[I − P, 1], [π, 0]] [h; g] = [r; 0], invertible when the
+        // stationary distribution is unique
+        let mut tri = TriMat::new((n + 1, n + 1));
+        for i in 0..n {
+            tri.add_triplet(i, i, 1.0);
+            tri.add_triplet(i, n, 1.0);
+            tri.add_triplet(n, i, pi[i]);
+        }
+        for (i, succ) in self.markov.successors().into_iter().enumerate() {
+            for (j, p) in succ {
+                tri.add_triplet(i, j, -p);
+            }
+        }
+        let bordered: CsMat<f64> = tri.to_csr();
+
+        let mut rhs = Array1::zeros(n + 1);
+        rhs.slice_mut(ndarray::s![..n]).assign(&self.reward.values);
+        let solution = SparseLu::factorize(&bordered)?.solve(&rhs)?;
+
+        Ok(AverageReward {
+            gain: solution[n],
+            bias: self.with_values(solution.slice_move(ndarray::s![..n])),
+            stationary,
+        })
+    }
+
+    fn with_values(&self, values: Array1<f64>) -> Vector<X> {
+        Vector {
+            values,
+            ix_map: self.reward.ix_map.clone(),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum RewardError {
+    #[error("discount factor must lie in [0, 1), got {0}")]
+    InvalidDiscount(f64),
+    #[error("chain has more than one closed class")]
+    Multichain,
+    #[error(transparent)]
+    Solve(#[from] SolveError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_state(a: f64, b: f64) -> Markov<i32, i32> {
+        Markov::from_matrix(Matrix::from_assoc(vec![
+            (0, 0, 1.0 - a),
+            (0, 1, a),
+            (1, 0, b),
+            (1, 1, 1.0 - b),
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_value_functions_of_two_state_chain() {
+        let (a, b) = (0.2, 0.6);
+        let markov = two_state(a, b);
+        let process = markov.with_rewards(Some(&Vector::from_assoc(vec![(0, 1.0)])), None);
+
+        // v = r + γPv
+        let gamma = 0.9;
+        let v = process.discounted(gamma).unwrap();
+        let v0 = v.get(&0).unwrap();
+        let v1 = v.get(&1).unwrap();
+        assert!((v0 - (1.0 + gamma * ((1.0 - a) * v0 + a * v1))).abs() < 1e-10);
+        assert!((v1 - gamma * (b * v0 + (1.0 - b) * v1)).abs() < 1e-10);
+
+        // g = π_0 = b / (a + b), and π·h = 0
+        let average = process.average().unwrap();
+        assert!((average.gain - b / (a + b)).abs() < 1e-10);
+        let h = &average.bias.values;
+        assert!(average.stationary.vector.values.dot(h).abs() < 1e-10);
+
+        // v_n / n tends to the gain, and v_n − n g to the bias
+        let horizon = 200;
+        let cumulative = process.cumulative(horizon);
+        let expected = horizon as f64 * average.gain + h[0];
+        assert!((cumulative.get(&0).unwrap() - expected).abs() < 1e-8);
+
+        assert_eq!(
+            process.discounted(1.0).unwrap_err(),
+            RewardError::InvalidDiscount(1.0)
+        );
+    }
+
+    #[test]
+    fn test_average_reward_with_transient_state() {
+        // 0 is transient and leads into the closed class {1, 2}
+        let (a, b) = (0.3, 0.6);
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            (0, 0, 0.2),
+            (0, 1, 0.8),
+            (1, 1, 1.0 - a),
+            (1, 2, a),
+            (2, 1, b),
+            (2, 2, 1.0 - b),
+        ]))
+        .unwrap();
+        let state = Vector::from_assoc(vec![(0, 5.0), (1, 1.0)]);
+        let average = markov.with_rewards(Some(&state), None).average().unwrap();
+
+        assert!((average.gain - b / (a + b)).abs() < 1e-10);
+        assert!(average.stationary.vector.get(&0).unwrap() < 1e-12);
+        // h_0 = r_0 − g + 0.2 h_0 + 0.8 h_1
+        let h = &average.bias.values;
+        assert!((h[0] - (5.0 - average.gain + 0.2 * h[0] + 0.8 * h[1])).abs() < 1e-10);
+
+        // Two absorbing states
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            (0, 0, 1.0),
+            (1, 1, 1.0),
+            (2, 0, 0.25),
+            (2, 1, 0.25),
+            (2, 2, 0.5),
+        ]))
+        .unwrap();
+        assert_eq!(
+            markov.with_rewards(None, None).average().unwrap_err(),
+            RewardError::Multichain
+        );
+    }
+
+    #[test]
+    fn test_transition_rewards_are_weighted_by_kernel() {
+        let markov = two_state(0.25, 0.5);
+        let transition = Matrix::from_assoc(vec![(0, 1, 4.0), (1, 1, -2.0)]);
+        let state = Vector::from_assoc(vec![(1, 1.0)]);
+        let process = markov.with_rewards(Some(&state), Some(&transition));
+
+        assert_eq!(process.reward.get(&0), Some(1.0));
+        assert_eq!(process.reward.get(&1), Some(0.0));
+        assert_eq!(process.cumulative(1).values, process.reward.values);
+    }
+}
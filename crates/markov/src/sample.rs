@@ -0,0 +1,353 @@
+use rand::Rng;
+use std::sync::Arc;
+
+use crate::ix_map::IxMap;
+use crate::markov::Markov;
+use crate::prob::Prob;
+
+/// Walker's alias table: O(n) to build, O(1) per draw.
+#[derive(Debug, Clone)]
+pub struct AliasTable {
+    threshold: Vec<f64>,
+    alias: Vec<usize>,
+}
+
+impl AliasTable {
+    /// Build from non-negative weights with a positive sum.
+    pub fn new(weights: &[f64]) -> Self {
+        let n = weights.len();
+        let total: f64 = weights.iter().sum();
+        let mut scaled: Vec<f64> = weights.iter().map(|w| w * n as f64 / total).collect();
+
+        let mut small = Vec::new();
+        let mut large = Vec::new();
+        for (i, &w) in scaled.iter().enumerate() {
+            if w < 1.0 {
+                small.push(i);
+            } else {
+                large.push(i);
+            }
+        }
+
+        let mut threshold = vec![1.0; n];
+        let mut alias: Vec<usize> = (0..n).collect();
+        while let (Some(s), Some(&l)) = (small.pop(), large.last()) {
+            threshold[s] = scaled[s];
+            alias[s] = l;
+            scaled[l] -= 1.0 - scaled[s];
+            if scaled[l] < 1.0 {
+                large.pop();
+                small.push(l);
+            }
+        }
+
+        Self { threshold, alias }
+    }
+
+    /// Draw an index with probability proportional to its weight.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        let i = rng.random_range(0..self.threshold.len());
+        if rng.random::<f64>() < self.threshold[i] {
+            i
+        } else {
+            self.alias[i]
+        }
+    }
+}
+
+/// Alias sampler over the labels of a distribution, for repeated draws.
+#[derive(Debug, Clone)]
+pub struct Sampler<X> {
+    table: AliasTable,
+    ix_map: Arc<IxMap<X>>,
+}
+
+impl<X> Sampler<X>
+where
+    X: Ord + Clone,
+{
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> X {
+        let i = self.table.sample(rng);
+        self.ix_map
+            .value_of(i)
+            .cloned()
+            .expect("alias index is a label")
+    }
+}
+
+/// One alias table per row of a kernel, for repeated steps.
+#[derive(Debug, Clone)]
+pub struct KernelSampler<X, Y> {
+    rows: Vec<(Vec<usize>, AliasTable)>,
+    x_ix_map: Arc<IxMap<X>>,
+    y_ix_map: Arc<IxMap<Y>>,
+}
+
+impl<X, Y> KernelSampler<X, Y>
+where
+    X: Ord + Clone,
+    Y: Ord + Clone,
+{
+    /// Draw the next state from row `x`; None if `x` is not a row label.
+    /// Scans the whole kernel for the row, so use `sampler` for repeated
+    /// steps.
+    pub fn step<R: Rng + ?Sized>(&self, x: &X, rng: &mut R) -> Option<Y> {
+        let (targets, table) = &self.rows[self.x_ix_map.index_of(x)?];
+        self.y_ix_map.value_of(targets[table.sample(rng)]).cloned()
+    }
+}
+
+/// Sequence of visited states.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trajectory<X> {
+    pub states: Vec<X>,
+}
+
+impl<X> Trajectory<X> {
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Consecutive (from, to) pairs.
+    pub fn transitions(&self) -> impl Iterator<Item = (&X, &X)> + '_ {
+        self.states.windows(2).map(|w| (&w[0], &w[1]))
+    }
+}
+
+/// Micro trajectory together with its image through an observable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JointTrajectory<X, Y> {
+    pub micro: Trajectory<X>,
+    pub observed: Trajectory<Y>,
+}
+
+impl<X> Prob<X>
+where
+    X: Ord + Clone,
+{
+    /// Draw a single label. Builds an alias table, so use `sampler` for
+    /// repeated draws.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> X {
+        self.sampler().sample(rng)
+    }
+
+    /// Alias sampler for repeated draws.
+    pub fn sampler(&self) -> Sampler<X> {
+        Sampler {
+            table: AliasTable::new(self.vector.values.as_slice().expect("contiguous values")),
+            ix_map: self.vector.ix_map.clone(),
+        }
+    }
+}
+
+impl<X, Y> Markov<X, Y>
+where
+    X: Ord + Clone,
+    Y: Ord + Clone,
+{
+    /// Draw the next state from row `x`; None if `x` is not a row label.
+    /// Scans the whole kernel for the row, so use `sampler` for repeated
+    /// steps.
+    pub fn step<R: Rng + ?Sized>(&self, x: &X, rng: &mut R) -> Option<Y> {
+        let (targets, table) = self.row_sampler(self.matrix.x_ix_map.index_of(x)?);
+        let j = targets[table.sample(rng)];
+        self.matrix.y_ix_map.value_of(j).cloned()
+    }
+
+    // Column indices and alias table of the non-zero entries of row i
+    fn row_sampler(&self, i: usize) -> (Vec<usize>, AliasTable) {
+        let (targets, weights): (Vec<usize>, Vec<f64>) = self
+            .matrix
+            .values
+            .iter()
+            .filter(|(val, (row, _))| *row == i && **val > 0.0)
+            .map(|(&val, (_, col))| (col, val))
+            .unzip();
+        (targets, AliasTable::new(&weights))
+    }
+
+    /// Alias samplers for every row, built once for repeated steps.
+    pub fn sampler(&self) -> KernelSampler<X, Y> {
+        KernelSampler {
+            rows: self.row_samplers(),
+            x_ix_map: self.matrix.x_ix_map.clone(),
+            y_ix_map: self.matrix.y_ix_map.clone(),
+        }
+    }
+
+    // One alias table per row, for long simulations
+    fn row_samplers(&self) -> Vec<(Vec<usize>, AliasTable)> {
+        let mut rows = vec![(Vec::new(), Vec::new()); self.matrix.x_ix_map.len()];
+        for (&val, (i, j)) in self.matrix.values.iter() {
+            if val > 0.0 {
+                rows[i].0.push(j);
+                rows[i].1.push(val);
+            }
+        }
+        rows.into_iter()
+            .map(|(targets, weights)| (targets, AliasTable::new(&weights)))
+            .collect()
+    }
+}
+
+impl<X> Markov<X, X>
+where
+    X: Ord + Clone,
+{
+    /// Simulate `length` states, starting from a draw of `initial`.
+    /// The path stops early if it reaches a target that has no row.
+    pub fn trajectory<R: Rng + ?Sized>(
+        &self,
+        initial: &Prob<X>,
+        length: usize,
+        rng: &mut R,
+    ) -> Trajectory<X> {
+        let x_map = &self.matrix.x_ix_map;
+        let y_map = &self.matrix.y_ix_map;
+        let col_to_row: Vec<Option<usize>> = (0..y_map.len())
+            .map(|j| y_map.value_of(j).and_then(|y| x_map.index_of(y)))
+            .collect();
+        let rows = self.row_samplers();
+
+        let mut states = Vec::with_capacity(length);
+        if length == 0 {
+            return Trajectory { states };
+        }
+
+        let mut current = x_map.index_of(&initial.sample(rng));
+        while let Some(i) = current {
+            states.push(x_map.value_of(i).cloned().expect("row index is a label"));
+            if states.len() == length {
+                break;
+            }
+            let (targets, table) = &rows[i];
+            current = col_to_row[targets[table.sample(rng)]];
+        }
+
+        Trajectory { states }
+    }
+
+    /// Simulate a micro trajectory and push each state through
+    /// `observable`, giving the paired macro trajectory. Micro states
+    /// without a row in `observable` end the path.
+    pub fn joint_trajectory<Y, R>(
+        &self,
+        observable: &Markov<X, Y>,
+        initial: &Prob<X>,
+        length: usize,
+        rng: &mut R,
+    ) -> JointTrajectory<X, Y>
+    where
+        Y: Ord + Clone,
+        R: Rng + ?Sized,
+    {
+        let micro = self.trajectory(initial, length, rng);
+        let rows = observable.row_samplers();
+
+        let mut states = Vec::with_capacity(micro.len());
+        for x in &micro.states {
+            let Some(i) = observable.matrix.x_ix_map.index_of(x) else {
+                break;
+            };
+            let (targets, table) = &rows[i];
+            let j = targets[table.sample(rng)];
+            states.extend(observable.matrix.y_ix_map.value_of(j).cloned());
+        }
+
+        let micro = Trajectory {
+            states: micro.states.into_iter().take(states.len()).collect(),
+        };
+        JointTrajectory {
+            micro,
+            observed: Trajectory { states },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Matrix, Vector};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn chain() -> Markov<&'static str, &'static str> {
+        Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "b", 1.0),
+            ("b", "a", 1.0),
+            ("b", "c", 3.0),
+            ("c", "a", 1.0),
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_alias_sampler_frequencies() {
+        let prob = Prob::from_vector(Vector::from_assoc(vec![("x", 0.1), ("y", 0.0), ("z", 0.9)]))
+            .unwrap();
+        let sampler = prob.sampler();
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let draws = 20_000;
+        let z = (0..draws)
+            .map(|_| sampler.sample(&mut rng))
+            .inspect(|x| assert_ne!(*x, "y"))
+            .filter(|x| *x == "z")
+            .count();
+        assert!((z as f64 / draws as f64 - 0.9).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_kernel_sampler_follows_rows() {
+        let markov = chain();
+        let sampler = markov.sampler();
+        let mut rng = StdRng::seed_from_u64(11);
+
+        let draws = 20_000;
+        let c = (0..draws)
+            .map(|_| sampler.step(&"b", &mut rng).unwrap())
+            .filter(|y| *y == "c")
+            .count();
+        assert!((c as f64 / draws as f64 - 0.75).abs() < 0.01);
+        assert_eq!(sampler.step(&"a", &mut rng), Some("b"));
+        assert_eq!(markov.step(&"c", &mut rng), Some("a"));
+        assert_eq!(sampler.step(&"z", &mut rng), None);
+    }
+
+    #[test]
+    fn test_trajectories_are_reproducible_from_seed() {
+        let markov = chain();
+        let initial =
+            Prob::from_vector(Vector::from_assoc(vec![("a", 1.0), ("b", 0.0), ("c", 0.0)]))
+                .unwrap();
+
+        let first = markov.trajectory(&initial, 100, &mut StdRng::seed_from_u64(42));
+        let second = markov.trajectory(&initial, 100, &mut StdRng::seed_from_u64(42));
+        assert_eq!(first, second);
+        assert_eq!(first.len(), 100);
+        assert_eq!(first.states[0], "a");
+        assert!(first
+            .transitions()
+            .all(|(x, y)| markov.matrix.get(x, y).unwrap_or(0.0) > 0.0));
+
+        let observable = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", 0, 1.0),
+            ("b", 0, 1.0),
+            ("c", 1, 1.0),
+        ]))
+        .unwrap();
+        let joint =
+            markov.joint_trajectory(&observable, &initial, 50, &mut StdRng::seed_from_u64(3));
+        assert_eq!(joint.micro.len(), joint.observed.len());
+        assert!(joint
+            .micro
+            .states
+            .iter()
+            .zip(&joint.observed.states)
+            .all(|(x, y)| (*x == "c") == (*y == 1)));
+    }
+}
@@ -0,0 +1,16 @@
+use num_traits::{NumAssign, Signed};
+use sprs::MulAcc;
+use std::fmt::Debug;
+
+/// Entry type of vectors and matrices: `f64` by default, `f32` for large
+/// chains, or an exact rational such as `num_rational::Rational64` for
+/// small ones, where comparisons with zero need no tolerance.
+pub trait Scalar:
+    NumAssign + Signed + Copy + PartialOrd + Default + Debug + MulAcc + Send + Sync + 'static
+{
+}
+
+impl<T> Scalar for T where
+    T: NumAssign + Signed + Copy + PartialOrd + Default + Debug + MulAcc + Send + Sync + 'static
+{
+}
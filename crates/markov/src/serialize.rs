@@ -0,0 +1,285 @@
+//! Serde support, behind the `serde` feature. Label maps are written as
+//! sorted label lists, vectors and matrices as their labels followed by
+//! the non-zero entries. Deserializing checks the same invariants as the
+//! constructors and reports their `BuildError`s. Serialized data also
+//! carries its own label lists and totals, which can contradict its
+//! entries; those failures are `DeserializeError`s.
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sprs::TriMat;
+use std::sync::Arc;
+
+use crate::ix_map::IxMap;
+use crate::markov::Markov;
+use crate::matrix::Matrix;
+use crate::prob::Prob;
+use crate::vector::Vector;
+
+/// Largest deviation of a total from one accepted for `Prob` and the rows
+/// of `Markov`. Accepted inputs are renormalized exactly.
+const NORMALIZATION_TOLERANCE: f64 = 1e-9;
+
+// Written so that a NaN total is rejected
+fn is_normalized(total: f64) -> bool {
+    (total - 1.0).abs() <= NORMALIZATION_TOLERANCE
+}
+
+/// Serialized data that contradicts itself.
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum DeserializeError {
+    #[error("entry label is not among the labels")]
+    UnknownLabel,
+    #[error("probabilities do not sum to one")]
+    NotNormalized,
+    #[error("rows do not sum to one")]
+    NotStochastic,
+}
+
+#[derive(Serialize, Deserialize)]
+struct VectorEntries<X> {
+    labels: Vec<X>,
+    entries: Vec<(X, f64)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MatrixEntries<X, Y> {
+    rows: Vec<X>,
+    cols: Vec<Y>,
+    entries: Vec<(X, Y, f64)>,
+}
+
+//##########################################################
+// IxMap
+//##########################################################
+
+impl<T> Serialize for IxMap<T>
+where
+    T: Ord + Clone + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(|(_, x)| x))
+    }
+}
+
+impl<'de, T> Deserialize<'de> for IxMap<T>
+where
+    T: Ord + Clone + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(IxMap::deserialize_from(Vec::deserialize(deserializer)?))
+    }
+}
+
+//##########################################################
+// Vector and Prob
+//##########################################################
+
+impl<X> Serialize for Vector<X>
+where
+    X: Ord + Clone + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        VectorEntries {
+            labels: self.ix_map.iter().map(|(_, x)| x.clone()).collect(),
+            entries: self.enumerate().filter(|(_, v)| *v != 0.0).collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, X> Deserialize<'de> for Vector<X>
+where
+    X: Ord + Clone + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = VectorEntries::<X>::deserialize(deserializer)?;
+        let ix_map = Arc::new(IxMap::deserialize_from(raw.labels));
+
+        let mut values = ndarray::Array1::zeros(ix_map.len());
+        for (x, v) in raw.entries {
+            let i = ix_map
+                .index_of(&x)
+                .ok_or_else(|| D::Error::custom(DeserializeError::UnknownLabel))?;
+            values[i] += v;
+        }
+        Ok(Vector { values, ix_map })
+    }
+}
+
+impl<X> Serialize for Prob<X>
+where
+    X: Ord + Clone + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.vector.serialize(serializer)
+    }
+}
+
+impl<'de, X> Deserialize<'de> for Prob<X>
+where
+    X: Ord + Clone + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let vector = Vector::<X>::deserialize(deserializer)?;
+        let total: f64 = vector.values().sum();
+        let prob = Prob::from_vector(vector).map_err(D::Error::custom)?;
+        if !is_normalized(total) {
+            return Err(D::Error::custom(DeserializeError::NotNormalized));
+        }
+        Ok(prob)
+    }
+}
+
+//##########################################################
+// Matrix and Markov
+//##########################################################
+
+impl<X, Y> Serialize for Matrix<X, Y>
+where
+    X: Ord + Clone + Serialize,
+    Y: Ord + Clone + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut entries: Vec<(X, Y, f64)> = self
+            .values
+            .iter()
+            .filter(|(v, _)| **v != 0.0)
+            .filter_map(|(&v, (i, j))| {
+                Some((
+                    self.x_ix_map.value_of(i)?.clone(),
+                    self.y_ix_map.value_of(j)?.clone(),
+                    v,
+                ))
+            })
+            .collect();
+        // Row-major order reads naturally as a list of transitions
+        entries.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+
+        MatrixEntries {
+            rows: self.x_ix_map.iter().map(|(_, x)| x.clone()).collect(),
+            cols: self.y_ix_map.iter().map(|(_, y)| y.clone()).collect(),
+            entries,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, X, Y> Deserialize<'de> for Matrix<X, Y>
+where
+    X: Ord + Clone + Deserialize<'de>,
+    Y: Ord + Clone + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = MatrixEntries::<X, Y>::deserialize(deserializer)?;
+        let x_ix_map = Arc::new(IxMap::deserialize_from(raw.rows));
+        let y_ix_map = Arc::new(IxMap::deserialize_from(raw.cols));
+
+        let mut trimat = TriMat::new((x_ix_map.len(), y_ix_map.len()));
+        for (x, y, v) in raw.entries {
+            match (x_ix_map.index_of(&x), y_ix_map.index_of(&y)) {
+                (Some(i), Some(j)) => trimat.add_triplet(i, j, v),
+                _ => return Err(D::Error::custom(DeserializeError::UnknownLabel)),
+            }
+        }
+        Ok(Matrix {
+            values: trimat.to_csc(),
+            x_ix_map,
+            y_ix_map,
+        })
+    }
+}
+
+impl<X, Y> Serialize for Markov<X, Y>
+where
+    X: Ord + Clone + Serialize,
+    Y: Ord + Clone + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.matrix.serialize(serializer)
+    }
+}
+
+impl<'de, X, Y> Deserialize<'de> for Markov<X, Y>
+where
+    X: Ord + Clone + Deserialize<'de>,
+    Y: Ord + Clone + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let matrix = Matrix::<X, Y>::deserialize(deserializer)?;
+        let row_sums = matrix.get_rows_sums();
+        let markov = Markov::from_matrix(matrix).map_err(D::Error::custom)?;
+        if row_sums.values().any(|s| !is_normalized(*s)) {
+            return Err(D::Error::custom(DeserializeError::NotStochastic));
+        }
+        Ok(markov)
+    }
+}
+
+impl<T: Ord + Clone> IxMap<T> {
+    // Labels may come in any order; repeated labels are merged
+    fn deserialize_from(mut labels: Vec<T>) -> Self {
+        labels.sort();
+        labels.dedup();
+        IxMap::from_distinct_sorted(labels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markov_round_trip() {
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "a", 1.0),
+            ("a", "b", 3.0),
+            ("b", "a", 1.0),
+        ]))
+        .unwrap();
+
+        let json = serde_json::to_string(&markov).unwrap();
+        let back: Markov<String, String> = serde_json::from_str(&json).unwrap();
+        let mut entries: Vec<_> = back.enumerate().collect();
+        entries.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        assert_eq!(
+            entries,
+            vec![
+                ("a".to_string(), "a".to_string(), 0.25),
+                ("a".to_string(), "b".to_string(), 0.75),
+                ("b".to_string(), "a".to_string(), 1.0),
+            ]
+        );
+
+        let pi = Prob::from_vector(Vector::from_assoc(vec![("a", 1.0), ("b", 0.0)])).unwrap();
+        let back: Prob<String> =
+            serde_json::from_str(&serde_json::to_string(&pi).unwrap()).unwrap();
+        assert_eq!(back.vector.len(), 2);
+        assert_eq!(back.prob(&"a".to_string()), Some(1.0));
+    }
+
+    #[test]
+    fn test_invalid_input_is_rejected() {
+        let unnormalized = r#"{"labels": ["a", "b"], "entries": [["a", 0.5], ["b", 0.6]]}"#;
+        let err = serde_json::from_str::<Prob<String>>(unnormalized).unwrap_err();
+        assert!(err.to_string().contains("sum to one"));
+
+        let substochastic =
+            r#"{"rows": [0, 1], "cols": [0], "entries": [[0, 0, 1.0], [1, 0, 0.5]]}"#;
+        let err = serde_json::from_str::<Markov<u8, u8>>(substochastic).unwrap_err();
+        assert!(err.to_string().contains("rows do not sum to one"));
+
+        let empty_row = r#"{"rows": [0, 1], "cols": [0], "entries": [[0, 0, 1.0]]}"#;
+        let err = serde_json::from_str::<Markov<u8, u8>>(empty_row).unwrap_err();
+        assert!(err.to_string().contains("zero total weight"));
+
+        let unknown = r#"{"rows": [0], "cols": [0], "entries": [[0, 1, 1.0]]}"#;
+        let err = serde_json::from_str::<Matrix<u8, u8>>(unknown).unwrap_err();
+        assert!(err.to_string().contains("not among the labels"));
+        let unknown = r#"{"labels": ["a"], "entries": [["b", 1.0]]}"#;
+        let err = serde_json::from_str::<Vector<String>>(unknown).unwrap_err();
+        assert!(err.to_string().contains("not among the labels"));
+
+        assert!(!is_normalized(f64::NAN));
+    }
+}
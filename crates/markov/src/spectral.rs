@@ -0,0 +1,384 @@
+use nalgebra::{Complex, DMatrix, DVector};
+
+use crate::markov::Markov;
+use crate::prob::Prob;
+use crate::vector::Vector;
+
+/// Detailed balance deviation below which a chain is treated as reversible.
+const REVERSIBILITY_TOLERANCE: f64 = 1e-10;
+
+/// Relative shift used by inverse iteration, to keep P − λI invertible.
+const INVERSE_ITERATION_SHIFT: f64 = 1e-10;
+
+const INVERSE_ITERATION_STEPS: usize = 3;
+
+/// Eigenvalues closer than this are treated as copies of one eigenvalue.
+const REPEATED_EIGENVALUE_TOLERANCE: f64 = 1e-8;
+
+/// Eigenvector with labelled real and imaginary parts.
+#[derive(Debug, Clone)]
+pub struct Eigenvector<X> {
+    pub re: Vector<X>,
+    pub im: Vector<X>,
+}
+
+/// Eigenvalue of a kernel with its left (lP = λl) and right (Pr = λr)
+/// eigenvectors, normalized so that Σ l_i r_i = 1.
+#[derive(Debug, Clone)]
+pub struct Eigenpair<X> {
+    pub value: Complex<f64>,
+    pub left: Eigenvector<X>,
+    pub right: Eigenvector<X>,
+}
+
+/// Leading eigenpairs of a kernel, by decreasing modulus.
+#[derive(Debug, Clone)]
+pub struct Spectrum<X> {
+    pub eigenpairs: Vec<Eigenpair<X>>,
+    /// Whether the symmetrized solver was used. Eigenvalues of reversible
+    /// chains are real.
+    pub reversible: bool,
+}
+
+impl<X> Spectrum<X> {
+    pub fn eigenvalues(&self) -> impl Iterator<Item = Complex<f64>> + '_ {
+        self.eigenpairs.iter().map(|e| e.value)
+    }
+
+    /// Second-largest eigenvalue modulus.
+    pub fn slem(&self) -> f64 {
+        self.eigenpairs.get(1).map_or(0.0, |e| e.value.norm())
+    }
+
+    /// Absolute spectral gap 1 − |λ₂|.
+    pub fn spectral_gap(&self) -> f64 {
+        1.0 - self.slem()
+    }
+
+    /// Relaxation time 1 / (1 − |λ₂|), in steps.
+    pub fn relaxation_time(&self) -> f64 {
+        1.0 / self.spectral_gap()
+    }
+
+    /// Implied timescales −1 / ln|λ_k| of the non-leading eigenvalues, in
+    /// steps. Unit-modulus eigenvalues give infinite timescales.
+    pub fn implied_timescales(&self) -> Vec<f64> {
+        self.eigenpairs
+            .iter()
+            .skip(1)
+            .map(|e| -1.0 / e.value.norm().ln())
+            .collect()
+    }
+}
+
+impl<X> Markov<X, X>
+where
+    X: Ord + Clone,
+{
+    /// Leading `k` eigenpairs of the kernel, using a dense solver.
+    ///
+    /// Reversible chains with respect to `stationary` are symmetrized as
+    /// D^½ P D^-½ with D = diag(π), which gives real eigenpairs. Otherwise
+    /// eigenvalues come from a Schur decomposition and eigenvectors from
+    /// inverse iteration.
+    pub fn spectrum(&self, k: usize, stationary: &Prob<X>) -> Spectrum<X> {
+        let dense = self.to_dense();
+        let pi = &stationary.vector.values;
+
+        let reversible = pi.len() == dense.nrows()
+            && pi.iter().all(|&p| p > 0.0)
+            && self.detailed_balance_deviation_sum(stationary) < REVERSIBILITY_TOLERANCE;
+
+        let mut pairs = if reversible {
+            symmetric_eigenpairs(&dense, &pi.to_vec())
+        } else {
+            general_eigenpairs(&dense, k)
+        };
+        pairs.sort_by(|a, b| {
+            b.0.norm()
+                .total_cmp(&a.0.norm())
+                .then(b.0.re.total_cmp(&a.0.re))
+        });
+        pairs.truncate(k);
+
+        let eigenpairs = pairs
+            .into_iter()
+            .map(|(value, left, right)| Eigenpair {
+                value,
+                left: self.eigenvector(&left),
+                right: self.eigenvector(&right),
+            })
+            .collect();
+
+        Spectrum {
+            eigenpairs,
+            reversible,
+        }
+    }
+
+    fn to_dense(&self) -> DMatrix<f64> {
+        let n = self.matrix.x_ix_map.len();
+        let mut dense = DMatrix::zeros(n, n);
+        for (i, succ) in self.successors().into_iter().enumerate() {
+            for (j, p) in succ {
+                dense[(i, j)] += p;
+            }
+        }
+        dense
+    }
+
+    fn eigenvector(&self, values: &DVector<Complex<f64>>) -> Eigenvector<X> {
+        let part = |f: fn(&Complex<f64>) -> f64| Vector {
+            values: values.iter().map(f).collect(),
+            ix_map: self.matrix.x_ix_map.clone(),
+        };
+        Eigenvector {
+            re: part(|c| c.re),
+            im: part(|c| c.im),
+        }
+    }
+}
+
+type RawEigenpair = (Complex<f64>, DVector<Complex<f64>>, DVector<Complex<f64>>);
+
+// Eigenpairs of a π-reversible kernel through its symmetrization.
+fn symmetric_eigenpairs(dense: &DMatrix<f64>, pi: &[f64]) -> Vec<RawEigenpair> {
+    let n = dense.nrows();
+    let sqrt_pi: Vec<f64> = pi.iter().map(|p| p.sqrt()).collect();
+    let symmetric = DMatrix::from_fn(n, n, |i, j| sqrt_pi[i] * dense[(i, j)] / sqrt_pi[j]);
+    let eigen = symmetric.symmetric_eigen();
+
+    (0..n)
+        .map(|k| {
+            let u = eigen.eigenvectors.column(k);
+            let left = DVector::from_fn(n, |i, _| Complex::from(u[i] * sqrt_pi[i]));
+            let right = DVector::from_fn(n, |i, _| Complex::from(u[i] / sqrt_pi[i]));
+            (Complex::from(eigen.eigenvalues[k]), left, right)
+        })
+        .collect()
+}
+
+// Leading eigenpairs of a general kernel: Schur eigenvalues, then inverse
+// iteration for the eigenvectors. Copies of a repeated eigenvalue, such as
+// λ = 1 with several closed classes, are deflated against the eigenvectors
+// already found for it, so each copy gets its own.
+fn general_eigenpairs(dense: &DMatrix<f64>, k: usize) -> Vec<RawEigenpair> {
+    let mut values: Vec<Complex<f64>> = dense.complex_eigenvalues().iter().copied().collect();
+    values.sort_by(|a, b| b.norm().total_cmp(&a.norm()).then(b.re.total_cmp(&a.re)));
+    values.truncate(k);
+
+    let complex = dense.map(Complex::from);
+    let transpose = complex.transpose();
+
+    // Eigenvalue with its left and right eigenvectors
+    type Eigenspace = (
+        Complex<f64>,
+        Vec<DVector<Complex<f64>>>,
+        Vec<DVector<Complex<f64>>>,
+    );
+    let mut eigenspaces: Vec<Eigenspace> = Vec::new();
+    for value in values {
+        let position = eigenspaces
+            .iter()
+            .position(|(v, _, _)| (v - value).norm() < REPEATED_EIGENVALUE_TOLERANCE);
+        let (value, lefts, rights) = match position {
+            Some(position) => &mut eigenspaces[position],
+            None => {
+                eigenspaces.push((value, Vec::new(), Vec::new()));
+                eigenspaces.last_mut().expect("just pushed")
+            }
+        };
+        let right = inverse_iteration(&complex, *value, rights);
+        let left = inverse_iteration(&transpose, *value, lefts);
+        rights.push(right);
+        lefts.push(left);
+    }
+
+    eigenspaces
+        .into_iter()
+        .flat_map(|(value, lefts, rights)| {
+            let lefts = biorthonormalize(lefts, &rights);
+            lefts
+                .into_iter()
+                .zip(rights)
+                .map(move |(left, right)| (value, left, right))
+        })
+        .collect()
+}
+
+// Inverse iteration for an eigenvector of `value`, kept orthogonal to the
+// orthonormal vectors in `found`.
+fn inverse_iteration(
+    matrix: &DMatrix<Complex<f64>>,
+    value: Complex<f64>,
+    found: &[DVector<Complex<f64>>],
+) -> DVector<Complex<f64>> {
+    let n = matrix.nrows();
+    let shift = value + INVERSE_ITERATION_SHIFT * value.norm().max(1.0);
+    let lu = (matrix - DMatrix::identity(n, n) * shift).lu();
+    let deflate = |mut vector: DVector<Complex<f64>>| {
+        for u in found {
+            let overlap = u.dotc(&vector);
+            vector -= u * overlap;
+        }
+        vector
+    };
+
+    // A non-constant start, so that deflating the constant vector leaves
+    // something to iterate on
+    let start = DVector::from_fn(n, |i, _| Complex::from(1.0 / (i + 1) as f64));
+    let mut vector = deflate(start);
+    vector /= Complex::from(vector.norm());
+    for _ in 0..INVERSE_ITERATION_STEPS {
+        let Some(next) = lu.solve(&vector) else {
+            break;
+        };
+        let next = deflate(next);
+        let norm = next.norm();
+        if norm == 0.0 || !norm.is_finite() {
+            break;
+        }
+        vector = next / Complex::from(norm);
+    }
+    vector
+}
+
+// Combine the left eigenvectors of one eigenvalue so that l_a · r_b = δ_ab.
+// Left unchanged when no such combination exists (defective eigenvalues).
+fn biorthonormalize(
+    lefts: Vec<DVector<Complex<f64>>>,
+    rights: &[DVector<Complex<f64>>],
+) -> Vec<DVector<Complex<f64>>> {
+    let m = lefts.len();
+    let overlaps = DMatrix::from_fn(m, m, |a, b| lefts[a].dot(&rights[b]));
+    let Some(inverse) = overlaps.try_inverse() else {
+        return lefts;
+    };
+    (0..m)
+        .map(|a| {
+            lefts
+                .iter()
+                .enumerate()
+                .fold(DVector::zeros(lefts[a].len()), |sum, (b, left)| {
+                    sum + left * inverse[(a, b)]
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Matrix;
+
+    #[test]
+    fn test_reversible_two_state_chain() {
+        // Eigenvalues 1 and 1 − a − b
+        let (a, b) = (0.2, 0.3);
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            (0, 0, 1.0 - a),
+            (0, 1, a),
+            (1, 0, b),
+            (1, 1, 1.0 - b),
+        ]))
+        .unwrap();
+        let pi = markov.stationary_direct(1e-12).unwrap().distribution;
+
+        let spectrum = markov.spectrum(2, &pi);
+
+        assert!(spectrum.reversible);
+        assert!((spectrum.eigenpairs[0].value.re - 1.0).abs() < 1e-12);
+        assert!((spectrum.slem() - 0.5).abs() < 1e-12);
+        assert!((spectrum.relaxation_time() - 2.0).abs() < 1e-10);
+        assert!((spectrum.implied_timescales()[0] + 1.0 / 0.5f64.ln()).abs() < 1e-10);
+
+        // Leading left eigenvector is proportional to π
+        let left = &spectrum.eigenpairs[0].left.re;
+        let ratio = left.values[0] / left.values[1];
+        assert!((ratio - pi.vector.values[0] / pi.vector.values[1]).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_rotation_has_complex_eigenvalues() {
+        // Deterministic three-cycle: eigenvalues are the cube roots of unity
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "b", 1.0),
+            ("b", "c", 1.0),
+            ("c", "a", 1.0),
+        ]))
+        .unwrap();
+        let pi = markov.stationary_direct(1e-12).unwrap().distribution;
+
+        let spectrum = markov.spectrum(3, &pi);
+
+        assert!(!spectrum.reversible);
+        assert!(spectrum
+            .eigenvalues()
+            .all(|v| (v.norm() - 1.0).abs() < 1e-10));
+        assert!(spectrum.eigenvalues().any(|v| v.im.abs() > 0.5));
+        assert!(spectrum.spectral_gap().abs() < 1e-10);
+
+        // Right eigenvectors satisfy P r = λ r
+        for pair in &spectrum.eigenpairs {
+            let r = |x: &str| {
+                let i = pair.right.re.ix_map.index_of(&x).unwrap();
+                Complex::new(pair.right.re.values[i], pair.right.im.values[i])
+            };
+            for (x, y) in [("a", "b"), ("b", "c"), ("c", "a")] {
+                assert!((r(y) - pair.value * r(x)).norm() < 1e-8);
+            }
+        }
+    }
+
+    #[test]
+    fn test_disconnected_classes_get_independent_eigenvectors() {
+        // Two closed, non-reversible three-cycles: λ = 1 twice
+        let mut entries = Vec::new();
+        for cycle in [["a", "b", "c"], ["d", "e", "f"]] {
+            for k in 0..3 {
+                entries.push((cycle[k], cycle[k], 0.2));
+                entries.push((cycle[k], cycle[(k + 1) % 3], 0.8));
+            }
+        }
+        let markov = Markov::from_matrix(Matrix::from_assoc(entries)).unwrap();
+        let pi = Prob::from_vector(Vector::from_assoc(
+            ["a", "b", "c", "d", "e", "f"].map(|x| (x, 1.0 / 6.0)),
+        ))
+        .unwrap();
+
+        let spectrum = markov.spectrum(2, &pi);
+
+        assert!(!spectrum.reversible);
+        let [first, second] = &spectrum.eigenpairs[..] else {
+            panic!("expected two eigenpairs");
+        };
+        assert!((first.value - 1.0).norm() < 1e-10);
+        assert!((second.value - 1.0).norm() < 1e-10);
+
+        // Right eigenvectors are constant on each class, but not the same
+        // vector; left and right ones are biorthonormal
+        let r = |pair: &Eigenpair<&str>, x: &str| pair.right.re.get(&x).unwrap();
+        let l = |pair: &Eigenpair<&str>, x: &str| pair.left.re.get(&x).unwrap();
+        for pair in [first, second] {
+            assert!((r(pair, "a") - r(pair, "c")).abs() < 1e-8);
+            assert!((r(pair, "d") - r(pair, "f")).abs() < 1e-8);
+        }
+        let det = r(first, "a") * r(second, "d") - r(first, "d") * r(second, "a");
+        assert!(det.abs() > 1e-3);
+        let states = ["a", "b", "c", "d", "e", "f"];
+        for (p, q, expected) in [
+            (first, first, 1.0),
+            (first, second, 0.0),
+            (second, first, 0.0),
+        ] {
+            let overlap: f64 = states.iter().map(|x| l(p, x) * r(q, x)).sum();
+            assert!((overlap - expected).abs() < 1e-8);
+        }
+
+        let coarsening = markov.pcca(2, &pi).unwrap();
+        let block = |x: &str| coarsening.partition.matrix.get(&x, &1).unwrap_or(0.0);
+        assert_eq!(block("a"), block("c"));
+        assert_eq!(block("d"), block("f"));
+        assert_ne!(block("a"), block("d"));
+    }
+}
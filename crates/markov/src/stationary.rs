@@ -0,0 +1,235 @@
+use ndarray::{linalg::Dot, Array1};
+use sprs::{CsMat, TriMat};
+
+use crate::linalg::{self, SolveError};
+use crate::markov::Markov;
+use crate::prob::Prob;
+use crate::vector::{max_difference, Vector};
+
+/// Algorithm used to obtain a stationary distribution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StationaryMethod {
+    /// Sparse linear solve of πP = π with Σπ = 1.
+    Direct,
+    /// Power iteration π ← πP.
+    Power,
+    /// Power iteration on the lazy chain αI + (1 − α)P.
+    LazyPower { laziness: f64 },
+}
+
+/// Stationary distribution together with convergence diagnostics.
+#[derive(Debug, Clone)]
+pub struct Stationary<X> {
+    pub distribution: Prob<X>,
+    pub method: StationaryMethod,
+    /// Number of iterations performed (zero for the direct solve).
+    pub iterations: usize,
+    /// Residual ‖πP − π‖∞ with respect to the original kernel.
+    pub residual: f64,
+    /// Whether the residual is below the requested tolerance.
+    pub converged: bool,
+}
+
+impl<X> Markov<X, X>
+where
+    X: Ord + Clone,
+{
+    /// Residual ‖πP − π‖∞ of a candidate stationary distribution.
+    pub fn stationary_residual(&self, prob: &Prob<X>) -> f64 {
+        max_difference(&prob.dot(self).vector, &prob.vector)
+    }
+
+    /// Solve πP = π with Σπ = 1 directly, by replacing one equation of
+    /// (Pᵀ − I)π = 0 with the normalization constraint.
+    /// Fails with `SolveError::NotSquare` unless rows and columns carry the
+    /// same labels, and with `SolveError::Singular` when the chain has more
+    /// than one closed class, since the stationary distribution is then not
+    /// unique.
+    pub fn stationary_direct(&self, tolerance: f64) -> Result<Stationary<X>, SolveError> {
+        let n = self.matrix.x_ix_map.len();
+        if !self.has_square_labels() {
+            return Err(SolveError::NotSquare);
+        }
+        let last = n - 1;
+
+        let mut tri = TriMat::new((n, n));
+        for (&val, (i, j)) in self.matrix.values.iter() {
+            if j != last {
+                tri.add_triplet(j, i, val);
+            }
+        }
+        for i in 0..last {
+            tri.add_triplet(i, i, -1.0);
+        }
+        for j in 0..n {
+            tri.add_triplet(last, j, 1.0);
+        }
+        let system: CsMat<f64> = tri.to_csr();
+
+        let mut rhs = Array1::zeros(n);
+        rhs[last] = 1.0;
+
+        let mut solution = linalg::solve(&system, &rhs)?;
+        // Round-off can leave tiny negative entries
+        solution.mapv_inplace(|p| p.max(0.0));
+
+        let vector = Vector {
+            values: solution,
+            ix_map: self.matrix.x_ix_map.clone(),
+        };
+        let distribution = Prob::from_vector(vector).map_err(|_| SolveError::Singular)?;
+        let residual = self.stationary_residual(&distribution);
+
+        Ok(Stationary {
+            distribution,
+            method: StationaryMethod::Direct,
+            iterations: 0,
+            residual,
+            converged: residual < tolerance,
+        })
+    }
+
+    /// Power iteration from `initial`. Does not converge on periodic chains;
+    /// use `stationary_lazy_power` for those.
+    pub fn stationary_power(
+        &self,
+        initial: &Prob<X>,
+        tolerance: f64,
+        max_iterations: usize,
+    ) -> Stationary<X> {
+        let (distribution, iterations) = self.iterate(initial, 0.0, tolerance, max_iterations);
+        let residual = self.stationary_residual(&distribution);
+
+        Stationary {
+            distribution,
+            method: StationaryMethod::Power,
+            iterations,
+            residual,
+            converged: residual < tolerance,
+        }
+    }
+
+    /// Power iteration on the lazy chain αI + (1 − α)P, which has the same
+    /// stationary distributions as P but is aperiodic for any 0 < α < 1.
+    pub fn stationary_lazy_power(
+        &self,
+        initial: &Prob<X>,
+        laziness: f64,
+        tolerance: f64,
+        max_iterations: usize,
+    ) -> Stationary<X> {
+        let laziness = laziness.clamp(0.0, 1.0);
+        let (distribution, iterations) = self.iterate(initial, laziness, tolerance, max_iterations);
+        let residual = self.stationary_residual(&distribution);
+
+        Stationary {
+            distribution,
+            method: StationaryMethod::LazyPower { laziness },
+            iterations,
+            residual,
+            converged: residual < tolerance,
+        }
+    }
+
+    // Iterate π ← απ + (1 − α)πP until successive iterates are within tolerance.
+    fn iterate(
+        &self,
+        initial: &Prob<X>,
+        laziness: f64,
+        tolerance: f64,
+        max_iterations: usize,
+    ) -> (Prob<X>, usize) {
+        let mut current = initial.clone();
+
+        for iteration in 1..=max_iterations {
+            let mut next = current.dot(self);
+            if laziness > 0.0 {
+                next.vector = &(&current.vector * laziness) + &(&next.vector * (1.0 - laziness));
+            }
+
+            let diff = max_difference(&current.vector, &next.vector);
+            current = next;
+            if diff < tolerance {
+                return (current, iteration);
+            }
+        }
+
+        (current, max_iterations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Matrix;
+
+    fn two_cycle() -> Markov<&'static str, &'static str> {
+        Markov::from_matrix(Matrix::from_assoc(vec![("a", "b", 1.0), ("b", "a", 1.0)])).unwrap()
+    }
+
+    #[test]
+    fn test_direct_solve_matches_known_equilibrium() {
+        // π = (2/5, 2/5, 1/5) for this chain
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "b", 1.0),
+            ("b", "a", 1.0),
+            ("b", "c", 1.0),
+            ("c", "a", 1.0),
+        ]))
+        .unwrap();
+
+        let result = markov.stationary_direct(1e-10).unwrap();
+
+        assert!(result.converged);
+        assert_eq!(result.method, StationaryMethod::Direct);
+        assert!((result.distribution.prob(&"a").unwrap() - 0.4).abs() < 1e-12);
+        assert!((result.distribution.prob(&"b").unwrap() - 0.4).abs() < 1e-12);
+        assert!((result.distribution.prob(&"c").unwrap() - 0.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_power_iteration_reports_non_convergence_on_two_cycle() {
+        let markov = two_cycle();
+        let initial = Prob::from_vector(Vector::from_assoc(vec![("a", 1.0), ("b", 0.0)])).unwrap();
+
+        let power = markov.stationary_power(&initial, 1e-8, 50);
+        assert!(!power.converged);
+        assert_eq!(power.iterations, 50);
+        assert!((power.residual - 1.0).abs() < 1e-12);
+
+        let lazy = markov.stationary_lazy_power(&initial, 0.5, 1e-8, 50);
+        assert!(lazy.converged);
+        assert!((lazy.distribution.prob(&"a").unwrap() - 0.5).abs() < 1e-8);
+
+        let direct = markov.stationary_direct(1e-10).unwrap();
+        assert!((direct.distribution.prob(&"a").unwrap() - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_direct_solve_rejects_reducible_chain() {
+        let markov =
+            Markov::from_matrix(Matrix::from_assoc(vec![("a", "a", 1.0), ("b", "b", 1.0)]))
+                .unwrap();
+
+        assert_eq!(
+            markov.stationary_direct(1e-10).unwrap_err(),
+            SolveError::Singular
+        );
+    }
+
+    #[test]
+    fn test_direct_solve_rejects_mismatched_labels() {
+        // Rows {a, b} and columns {a, c}: as many of each, but not the same
+        let markov = Markov::from_matrix(Matrix::from_assoc(vec![
+            ("a", "a", 0.5),
+            ("a", "c", 0.5),
+            ("b", "a", 1.0),
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            markov.stationary_direct(1e-10).unwrap_err(),
+            SolveError::NotSquare
+        );
+    }
+}
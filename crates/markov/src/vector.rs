@@ -1,35 +1,37 @@
 use ndarray::{linalg::Dot, Array1};
 use std::collections::BTreeMap;
 use std::ops::{Add, Div, Mul, Sub};
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::ix_map::IxMap;
+use crate::scalar::Scalar;
 
 //##########################################################
 // Struct
 //##########################################################
 
-/// Vector with a bidirectional map for labels X.
+/// Vector with a bidirectional map for labels X and entries S.
 #[derive(Debug, Clone)]
-pub struct Vector<X> {
-    pub values: Array1<f64>,
-    pub ix_map: Rc<IxMap<X>>,
+pub struct Vector<X, S = f64> {
+    pub values: Array1<S>,
+    pub ix_map: Arc<IxMap<X>>,
 }
 
 //##########################################################
 // Impls
 //##########################################################
 
-impl<X> Vector<X>
+impl<X, S> Vector<X, S>
 where
     X: Ord + Clone,
+    S: Scalar,
 {
-    // Build from an association list
-    pub fn from_assoc(assoc: impl IntoIterator<Item = (X, f64)>) -> Self {
-        let mut map: BTreeMap<X, f64> = BTreeMap::new();
+    // Build from an association list, for any scalar type
+    pub fn from_entries(assoc: impl IntoIterator<Item = (X, S)>) -> Self {
+        let mut map: BTreeMap<X, S> = BTreeMap::new();
 
         for (x, n) in assoc.into_iter() {
-            *map.entry(x).or_insert(0.0) += n;
+            *map.entry(x).or_insert(S::zero()) += n;
         }
 
         let size = map.len();
@@ -45,19 +47,19 @@ where
 
         Self {
             values,
-            ix_map: Rc::new(ix_map),
+            ix_map: Arc::new(ix_map),
         }
     }
 
     // Build from an manual association list
     pub fn unsafe_from_assoc<'a>(
-        ix_map: &Rc<IxMap<X>>,
+        ix_map: &Arc<IxMap<X>>,
         ixes: impl IntoIterator<Item = &'a usize>,
-        vals: impl IntoIterator<Item = &'a f64>,
+        vals: impl IntoIterator<Item = &'a S>,
     ) -> Self {
         let mut values = Array1::zeros(ix_map.len());
 
-        for (r, v) in ixes.into_iter().zip(vals.into_iter()) {
+        for (r, v) in ixes.into_iter().zip(vals) {
             values[*r] = *v;
         }
 
@@ -78,7 +80,7 @@ where
     }
 
     /// Get value at label x if `x` is known; otherwise None.
-    pub fn get(&self, x: &X) -> Option<f64> {
+    pub fn get(&self, x: &X) -> Option<S> {
         self.ix_map
             .index_of(x)
             .and_then(|i| self.values.get(i))
@@ -88,22 +90,113 @@ where
     // Map each value, mutably
     pub fn mapv_inplace<F>(&mut self, f: F)
     where
-        F: FnMut(f64) -> f64,
+        F: FnMut(S) -> S,
     {
         self.values.mapv_inplace(f);
     }
 
     /// Enumerate all values.
-    pub fn values(&self) -> impl Iterator<Item = &f64> + '_ {
+    pub fn values(&self) -> impl Iterator<Item = &S> + '_ {
         self.values.iter()
     }
 
     /// Enumerate all (label, value) pairs.
-    pub fn enumerate(&self) -> impl Iterator<Item = (X, f64)> + '_ {
+    pub fn enumerate(&self) -> impl Iterator<Item = (X, S)> + '_ {
         (0..self.values.len())
             .filter_map(move |i| self.ix_map.value_of(i).map(|x| (x.clone(), self.values[i])))
     }
 
+    /// Same values laid out along `ix_map`. Labels missing from `self` take
+    /// `fill`, and labels not in `ix_map` are dropped.
+    pub fn reindex(&self, ix_map: &Arc<IxMap<X>>, fill: S) -> Vector<X, S> {
+        if self.same_labels(ix_map) {
+            return Vector {
+                values: self.values.clone(),
+                ix_map: ix_map.clone(),
+            };
+        }
+        let values = ix_map
+            .iter()
+            .map(|(_, x)| self.get(x).unwrap_or(fill))
+            .collect();
+        Vector {
+            values,
+            ix_map: ix_map.clone(),
+        }
+    }
+
+    /// Both vectors laid out along a common label set chosen by `alignment`.
+    pub fn align(
+        &self,
+        other: &Vector<X, S>,
+        alignment: Alignment<S>,
+    ) -> Result<Aligned<X, S>, AlignmentError> {
+        if self.same_labels(&other.ix_map) {
+            return Ok((self.clone(), other.reindex(&self.ix_map, S::zero())));
+        }
+        let (ix_map, fill) = match alignment {
+            Alignment::Exact => return Err(AlignmentError::LabelMismatch),
+            Alignment::Union { fill } => (self.ix_map.union(&other.ix_map), fill),
+            Alignment::Intersection => (self.ix_map.intersection(&other.ix_map), S::zero()),
+        };
+        let ix_map = Arc::new(ix_map);
+        Ok((self.reindex(&ix_map, fill), other.reindex(&ix_map, fill)))
+    }
+
+    /// `self + other` after aligning labels.
+    pub fn checked_add(
+        &self,
+        other: &Vector<X, S>,
+        alignment: Alignment<S>,
+    ) -> Result<Vector<X, S>, AlignmentError> {
+        let (a, b) = self.align(other, alignment)?;
+        Ok(&a + &b)
+    }
+
+    /// `self - other` after aligning labels.
+    pub fn checked_sub(
+        &self,
+        other: &Vector<X, S>,
+        alignment: Alignment<S>,
+    ) -> Result<Vector<X, S>, AlignmentError> {
+        let (a, b) = self.align(other, alignment)?;
+        Ok(&a - &b)
+    }
+
+    /// Entrywise `self * other` after aligning labels.
+    pub fn checked_mul(
+        &self,
+        other: &Vector<X, S>,
+        alignment: Alignment<S>,
+    ) -> Result<Vector<X, S>, AlignmentError> {
+        let (a, b) = self.align(other, alignment)?;
+        Ok(&a * &b)
+    }
+
+    /// `self · other` after aligning labels.
+    pub fn checked_dot(
+        &self,
+        other: &Vector<X, S>,
+        alignment: Alignment<S>,
+    ) -> Result<S, AlignmentError> {
+        let (a, b) = self.align(other, alignment)?;
+        Ok(a.dot(&b))
+    }
+
+    fn same_labels(&self, ix_map: &Arc<IxMap<X>>) -> bool {
+        Arc::ptr_eq(&self.ix_map, ix_map) || *self.ix_map == **ix_map
+    }
+}
+
+impl<X> Vector<X>
+where
+    X: Ord + Clone,
+{
+    // Build from an association list of f64 values
+    pub fn from_assoc(assoc: impl IntoIterator<Item = (X, f64)>) -> Self {
+        Self::from_entries(assoc)
+    }
+
     pub fn norm(&self) -> f64 {
         self.values.iter().map(|x| *x * *x).sum::<f64>().sqrt()
     }
@@ -117,6 +210,20 @@ where
     }
 }
 
+// Two vectors laid out along the same labels
+type Aligned<X, S> = (Vector<X, S>, Vector<X, S>);
+
+/// How to combine vectors whose labels differ.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Alignment<S = f64> {
+    /// Labels must be identical.
+    Exact,
+    /// Every label of either vector, with `fill` where one of them has none.
+    Union { fill: S },
+    /// Only the labels present in both vectors.
+    Intersection,
+}
+
 /// Compute the maximum absolute difference between two vectors.
 pub fn max_difference<X>(v1: &Vector<X>, v2: &Vector<X>) -> f64
 where
@@ -164,22 +271,30 @@ where
 // Traits
 //##########################################################
 
-impl<X> Dot<Vector<X>> for Vector<X>
+// The operators zip raw values and keep the labels of the left operand;
+// use the `checked_*` methods when the label sets may differ.
+
+impl<X, S> Dot<Vector<X, S>> for Vector<X, S>
 where
     X: Ord,
+    S: Scalar,
 {
-    type Output = f64;
-    fn dot(&self, rhs: &Vector<X>) -> f64 {
-        self.values.dot(&rhs.values)
+    type Output = S;
+    fn dot(&self, rhs: &Vector<X, S>) -> S {
+        self.values
+            .iter()
+            .zip(&rhs.values)
+            .fold(S::zero(), |acc, (a, b)| acc + *a * *b)
     }
 }
 
-impl<'b, X> Add<&'b Vector<X>> for &Vector<X>
+impl<'b, X, S> Add<&'b Vector<X, S>> for &Vector<X, S>
 where
     X: Clone,
+    S: Scalar,
 {
-    type Output = Vector<X>;
-    fn add(self, rhs: &'b Vector<X>) -> Self::Output {
+    type Output = Vector<X, S>;
+    fn add(self, rhs: &'b Vector<X, S>) -> Self::Output {
         Vector {
             ix_map: self.ix_map.clone(),
             values: &self.values + &rhs.values,
@@ -187,12 +302,13 @@ where
     }
 }
 
-impl<'b, X> Sub<&'b Vector<X>> for &Vector<X>
+impl<'b, X, S> Sub<&'b Vector<X, S>> for &Vector<X, S>
 where
     X: Clone,
+    S: Scalar,
 {
-    type Output = Vector<X>;
-    fn sub(self, rhs: &'b Vector<X>) -> Self::Output {
+    type Output = Vector<X, S>;
+    fn sub(self, rhs: &'b Vector<X, S>) -> Self::Output {
         Vector {
             ix_map: self.ix_map.clone(),
             values: &self.values - &rhs.values,
@@ -200,12 +316,13 @@ where
     }
 }
 
-impl<'b, X> Mul<&'b Vector<X>> for &Vector<X>
+impl<'b, X, S> Mul<&'b Vector<X, S>> for &Vector<X, S>
 where
     X: Clone,
+    S: Scalar,
 {
-    type Output = Vector<X>;
-    fn mul(self, rhs: &'b Vector<X>) -> Self::Output {
+    type Output = Vector<X, S>;
+    fn mul(self, rhs: &'b Vector<X, S>) -> Self::Output {
         Vector {
             ix_map: self.ix_map.clone(),
             values: &self.values * &rhs.values,
@@ -213,32 +330,40 @@ where
     }
 }
 
-impl<X> Mul<f64> for &Vector<X>
+impl<X, S> Mul<S> for &Vector<X, S>
 where
     X: Clone,
+    S: Scalar,
 {
-    type Output = Vector<X>;
-    fn mul(self, rhs: f64) -> Self::Output {
+    type Output = Vector<X, S>;
+    fn mul(self, rhs: S) -> Self::Output {
         Vector {
             ix_map: self.ix_map.clone(),
-            values: &self.values * rhs,
+            values: self.values.mapv(|v| v * rhs),
         }
     }
 }
 
-impl<X> Div<f64> for &Vector<X>
+impl<X, S> Div<S> for &Vector<X, S>
 where
     X: Clone,
+    S: Scalar,
 {
-    type Output = Vector<X>;
-    fn div(self, rhs: f64) -> Self::Output {
+    type Output = Vector<X, S>;
+    fn div(self, rhs: S) -> Self::Output {
         Vector {
             ix_map: self.ix_map.clone(),
-            values: &self.values / rhs,
+            values: self.values.mapv(|v| v / rhs),
         }
     }
 }
 
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum AlignmentError {
+    #[error("vector labels do not match")]
+    LabelMismatch,
+}
+
 //##########################################################
 // Tests
 //##########################################################
@@ -274,4 +399,37 @@ mod tests {
         assert!(max_difference(&result[1], &expected1) < 1e-10);
         assert_eq!(rank(result), 1);
     }
+
+    #[test]
+    fn test_checked_ops_align_labels() {
+        let v1 = Vector::from_assoc(vec![("a", 1.0), ("b", 2.0)]);
+        let v2 = Vector::from_assoc(vec![("b", 3.0), ("c", 4.0)]);
+
+        assert_eq!(
+            v1.checked_add(&v2, Alignment::Exact).unwrap_err(),
+            AlignmentError::LabelMismatch
+        );
+
+        let sum = v1.checked_add(&v2, Alignment::Union { fill: 0.0 }).unwrap();
+        assert_eq!(
+            sum.enumerate().collect::<Vec<_>>(),
+            vec![("a", 1.0), ("b", 5.0), ("c", 4.0)]
+        );
+
+        let dot = v1.checked_dot(&v2, Alignment::Intersection).unwrap();
+        assert_eq!(dot, 6.0);
+
+        // Same labels from different maps need no alignment
+        let v3 = Vector::from_assoc(vec![("b", 1.0), ("a", 1.0)]);
+        assert_eq!(
+            v1.checked_sub(&v3, Alignment::Exact).unwrap().get(&"b"),
+            Some(1.0)
+        );
+
+        let reindexed = v2.reindex(&v1.ix_map, -1.0);
+        assert_eq!(
+            reindexed.enumerate().collect::<Vec<_>>(),
+            vec![("a", -1.0), ("b", 3.0)]
+        );
+    }
 }
@@ -1,9 +1,12 @@
 use crate::effects::Effect;
-use crate::graph_state::{ObservableNode, ObservableNodeType, StateNode};
+use crate::graph_state::{
+    ObservableNode, ObservableNodeType, StateNode, decode_observed_sequence, suggest_observable,
+};
 use crate::layout_settings::{BipartiteTabLayoutSettings, CircularTabLayoutSettings};
-use crate::store::{ActiveTab, EditMode, Store};
+use crate::store::{ActiveTab, EditMode, Store, SuggestObservableDialog};
 use eframe::egui;
 use petgraph::stable_graph::{EdgeIndex, NodeIndex};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
@@ -40,6 +43,13 @@ pub enum Action {
         node_idx: NodeIndex,
         new_weight: f64,
     },
+    /// Update the reward editor for a state graph node
+    UpdateStateNodeRewardEditor { node_idx: NodeIndex, value: String },
+    /// Set or clear the reward of a state graph node
+    UpdateStateNodeReward {
+        node_idx: NodeIndex,
+        reward: Option<f64>,
+    },
     /// Update the label editor for a state graph node
     UpdateStateNodeLabelEditor { node_idx: NodeIndex, value: String },
     /// Set the selection state of a state graph node
@@ -77,6 +87,16 @@ pub enum Action {
     SelectObservableNode { node_idx: NodeIndex, selected: bool },
     /// Set the selection state of an observed graph node (cached)
     SelectObservedNode { node_idx: NodeIndex, selected: bool },
+    /// Open, edit or close (None) the suggest observable dialog
+    SetSuggestObservableDialog {
+        dialog: Option<SuggestObservableDialog>,
+    },
+    /// Replace the Destination nodes and edges by a PCCA+ coarse-graining
+    ApplySuggestedObservable { macrostates: usize, fuzzy: bool },
+    /// Set the text of the observed sequence to decode
+    SetObservedSequenceInput { text: String },
+    /// Infer the micro states behind the observed sequence
+    DecodeObservedSequence,
 
     // Observable Edge Actions
     /// Add a observable edge from Source to Destination
@@ -121,6 +141,12 @@ pub enum Action {
     SetHeatmapEditingCell { cell: Option<(usize, usize)> },
     /// Set the text buffer for heatmap editing
     SetHeatmapEditBuffer { buffer: String },
+    /// Toggle the time-reversed chain view in a tab
+    SetShowReversed { tab: ActiveTab, reversed: bool },
+    /// Toggle colouring state nodes by their value function
+    SetShowValues { show: bool },
+    /// Set the discount factor of the value function
+    SetDiscount { gamma: f64 },
 
     // File Operations
     /// Save current project to file
@@ -140,6 +166,7 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
             let node_idx = store.state.graph.get_mut().add_node(StateNode {
                 name: name.clone(),
                 weight,
+                reward: None,
             });
             if let Some(node) = store.state.graph.get_mut().node_mut(node_idx) {
                 node.set_label(name.clone());
@@ -155,6 +182,7 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
                 node.set_label(name);
             }
 
+            store.sequence_posterior = None;
             vec![]
         }
         Action::RemoveStateNode { node_idx } => {
@@ -182,6 +210,7 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
                 store.observable.graph.get_mut().remove_node(source_idx);
             }
 
+            store.sequence_posterior = None;
             vec![]
         }
         Action::RenameStateNode { node_idx, new_name } => {
@@ -214,6 +243,7 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
                 node.set_label(new_name);
             }
 
+            store.sequence_posterior = None;
             vec![]
         }
         Action::UpdateStateNodeWeightEditor { node_idx, value } => {
@@ -227,6 +257,17 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
             if let Some(node) = store.state.graph.get_mut().node_mut(node_idx) {
                 node.payload_mut().weight = new_weight;
             }
+            store.sequence_posterior = None;
+            vec![]
+        }
+        Action::UpdateStateNodeRewardEditor { node_idx, value } => {
+            store.reward_editor.focus(node_idx, value);
+            vec![]
+        }
+        Action::UpdateStateNodeReward { node_idx, reward } => {
+            if let Some(node) = store.state.graph.get_mut().node_mut(node_idx) {
+                node.payload_mut().reward = reward;
+            }
             vec![]
         }
         Action::UpdateStateNodeLabelEditor { node_idx, value } => {
@@ -236,7 +277,7 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
         Action::SelectStateNode { node_idx, selected } => {
             if selected {
                 // Collect all node indices first to avoid borrow conflicts
-                let graph = store.state.graph.get_mut();
+                let graph = store.state.graph.get_mut_untracked();
                 let all_indices: Vec<_> = graph.g().node_indices().collect();
 
                 // Deselect all other nodes first
@@ -253,7 +294,7 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
                 }
             } else {
                 // Just deselect the target node
-                if let Some(node) = store.state.graph.get_mut().node_mut(node_idx) {
+                if let Some(node) = store.state.graph.get_mut_untracked().node_mut(node_idx) {
                     node.set_selected(false);
                 }
             }
@@ -283,10 +324,12 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
                 weight,
                 String::new(),
             );
+            store.sequence_posterior = None;
             vec![]
         }
         Action::RemoveStateEdgeByIndex { edge_idx } => {
             store.state.graph.get_mut().remove_edge(edge_idx);
+            store.sequence_posterior = None;
             vec![]
         }
         Action::UpdateStateEdgeWeightFromHeatmap {
@@ -322,6 +365,7 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
                     String::new(),
                 );
             }
+            store.sequence_posterior = None;
             vec![]
         }
 
@@ -335,10 +379,12 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
             if let Some(node) = store.observable.graph.get_mut().node_mut(node_idx) {
                 node.set_label(name);
             }
+            store.sequence_posterior = None;
             vec![]
         }
         Action::RemoveObservableDestinationNode { node_idx } => {
             store.observable.graph.get_mut().remove_node(node_idx);
+            store.sequence_posterior = None;
             vec![]
         }
         Action::UpdateObservableDestinationNodeLabelEditor { node_idx, value } => {
@@ -350,12 +396,13 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
                 node.payload_mut().name = new_name.clone();
                 node.set_label(new_name);
             }
+            store.sequence_posterior = None;
             vec![]
         }
         Action::SelectObservableNode { node_idx, selected } => {
             if selected {
                 // Collect all node indices first to avoid borrow conflicts
-                let graph = store.observable.graph.get_mut();
+                let graph = store.observable.graph.get_mut_untracked();
                 let all_indices: Vec<_> = graph.g().node_indices().collect();
 
                 // Deselect all other nodes first
@@ -372,7 +419,12 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
                 }
             } else {
                 // Just deselect the target node
-                if let Some(node) = store.observable.graph.get_mut().node_mut(node_idx) {
+                if let Some(node) = store
+                    .observable
+                    .graph
+                    .get_mut_untracked()
+                    .node_mut(node_idx)
+                {
                     node.set_selected(false);
                 }
             }
@@ -384,6 +436,62 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
             store.observed_node_selection = Some((node_idx, selected));
             vec![]
         }
+        Action::SetSuggestObservableDialog { dialog } => {
+            store.suggest_observable = dialog;
+            vec![]
+        }
+        Action::ApplySuggestedObservable { macrostates, fuzzy } => {
+            let assignment = match suggest_observable(store.state.graph.get(), macrostates, fuzzy) {
+                Ok(assignment) => assignment,
+                Err(e) => {
+                    store.error_message = Some(format!("Failed to suggest observable: {e}"));
+                    return vec![];
+                }
+            };
+            store.suggest_observable = None;
+
+            let graph = store.observable.graph.get_mut();
+            let mut sources = HashMap::new();
+            let mut destinations = Vec::new();
+            for (idx, node) in graph.nodes_iter() {
+                match node.payload().node_type {
+                    ObservableNodeType::Source => {
+                        if let Some(state_idx) = node.payload().state_node_idx {
+                            sources.insert(state_idx, idx);
+                        }
+                    }
+                    ObservableNodeType::Destination => destinations.push(idx),
+                }
+            }
+            // Removing a Destination node also removes its edges
+            for idx in destinations {
+                graph.remove_node(idx);
+            }
+
+            let count = assignment.iter().map(|(_, c, _)| c + 1).max().unwrap_or(0);
+            let values: Vec<NodeIndex> = (0..count)
+                .map(|c| {
+                    let name = format!("Value {c}");
+                    let node_idx = graph.add_node(ObservableNode {
+                        name: name.clone(),
+                        node_type: ObservableNodeType::Destination,
+                        state_node_idx: None,
+                    });
+                    if let Some(node) = graph.node_mut(node_idx) {
+                        node.set_label(name);
+                    }
+                    node_idx
+                })
+                .collect();
+
+            for (state_idx, c, weight) in assignment {
+                if let Some(&source_idx) = sources.get(&state_idx) {
+                    graph.add_edge_with_label(source_idx, values[c], weight, String::new());
+                }
+            }
+            store.sequence_posterior = None;
+            vec![]
+        }
 
         // Observable Edge Actions
         Action::AddObservableEdge {
@@ -403,10 +511,12 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
                     String::new(),
                 );
             }
+            store.sequence_posterior = None;
             vec![]
         }
         Action::RemoveObservableEdgeByIndex { edge_idx } => {
             store.observable.graph.get_mut().remove_edge(edge_idx);
+            store.sequence_posterior = None;
             vec![]
         }
         Action::UpdateObservableEdgeWeightFromHeatmap {
@@ -446,6 +556,26 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
                     String::new(),
                 );
             }
+            store.sequence_posterior = None;
+            vec![]
+        }
+
+        Action::SetObservedSequenceInput { text } => {
+            store.observed_sequence_input = text;
+            vec![]
+        }
+        Action::DecodeObservedSequence => {
+            match decode_observed_sequence(
+                store.state.graph.get(),
+                store.observable.graph.get(),
+                &store.observed_sequence_input,
+            ) {
+                Ok(posterior) => store.sequence_posterior = Some(posterior),
+                Err(e) => {
+                    store.sequence_posterior = None;
+                    store.error_message = Some(format!("Failed to decode sequence: {e}"));
+                }
+            }
             vec![]
         }
 
@@ -454,11 +584,15 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
             store.prev_mode = store.mode;
             store.mode = mode;
             if store.mode != EditMode::EdgeEditor {
-                store.state.graph.get_mut().set_selected_edges(Vec::new());
+                store
+                    .state
+                    .graph
+                    .get_mut_untracked()
+                    .set_selected_edges(Vec::new());
                 store
                     .observable
                     .graph
-                    .get_mut()
+                    .get_mut_untracked()
                     .set_selected_edges(Vec::new());
             }
             vec![]
@@ -482,14 +616,18 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
             vec![]
         }
         Action::ClearEdgeSelections => {
-            store.state.graph.get_mut().set_selected_edges(Vec::new());
+            store
+                .state
+                .graph
+                .get_mut_untracked()
+                .set_selected_edges(Vec::new());
             vec![]
         }
         Action::ClearObservableEdgeSelections => {
             store
                 .observable
                 .graph
-                .get_mut()
+                .get_mut_untracked()
                 .set_selected_edges(Vec::new());
             vec![]
         }
@@ -516,6 +654,22 @@ pub fn update(store: &mut Store, action: Action) -> Vec<Effect> {
             store.heatmap_edit_buffer = buffer;
             vec![]
         }
+        Action::SetShowReversed { tab, reversed } => {
+            match tab {
+                ActiveTab::DynamicalSystem => store.state.show_reversed = reversed,
+                ActiveTab::ObservedDynamics => store.observed.show_reversed = reversed,
+                ActiveTab::ObservableEditor => {}
+            }
+            vec![]
+        }
+        Action::SetShowValues { show } => {
+            store.state.show_values = show;
+            vec![]
+        }
+        Action::SetDiscount { gamma } => {
+            store.state.discount.set(gamma);
+            vec![]
+        }
 
         // File Operations
         Action::SaveToFile { path } => {
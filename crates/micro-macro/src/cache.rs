@@ -1,12 +1,17 @@
 use crate::graph_state::{
-    ObservableNodeType, calculate_observed_graph, compute_input_statistics,
-    compute_output_statistics,
+    ObservableNodeType, build_state_markov, calculate_observed_graph, compute_equilibrium,
+    compute_input_statistics, compute_output_statistics, compute_value_function,
+};
+use crate::graph_view::{
+    self, GraphDisplay, ObservableGraphDisplay, ObservedGraphDisplay, StateGraphDisplay,
 };
-use crate::graph_view::{GraphDisplay, ObservedGraphDisplay};
 use crate::heatmap::HeatmapData;
-use crate::store::Store;
+use crate::store::{Store, collect_sorted_weights_from_display, compute_generic_heatmap_data};
 use crate::versioned::Memoized;
-use markov::{Prob, Vector};
+use markov::{
+    Absorption, BlockEntropies, ClassDecomposition, EffectiveInformation, EntropyProduction,
+    HittingTimes, Markov, Prob, Vector,
+};
 use ndarray::linalg::Dot;
 use petgraph::{Direction, stable_graph::NodeIndex};
 use serde::{Deserialize, Serialize};
@@ -15,23 +20,89 @@ use std::collections::HashMap;
 /// Validation issues for state graph
 #[derive(Debug, Clone)]
 pub enum StateValidationIssue {
-    NoOutgoingEdges { node: NodeIndex, name: String },
-    NoIncomingEdges { node: NodeIndex, name: String },
+    NoOutgoingEdges {
+        node: NodeIndex,
+        name: String,
+    },
+    NoIncomingEdges {
+        node: NodeIndex,
+        name: String,
+    },
+    TransientClass {
+        node: NodeIndex,
+        names: Vec<String>,
+    },
+    PeriodicClass {
+        node: NodeIndex,
+        names: Vec<String>,
+        period: usize,
+    },
+    MultipleClosedClasses {
+        node: NodeIndex,
+        count: usize,
+    },
+}
+
+impl StateValidationIssue {
+    /// Node to select when the issue is clicked
+    pub fn node(&self) -> NodeIndex {
+        match self {
+            StateValidationIssue::NoOutgoingEdges { node, .. }
+            | StateValidationIssue::NoIncomingEdges { node, .. }
+            | StateValidationIssue::TransientClass { node, .. }
+            | StateValidationIssue::PeriodicClass { node, .. }
+            | StateValidationIssue::MultipleClosedClasses { node, .. } => *node,
+        }
+    }
+
+    /// Warnings describe the chain structure but do not block computation
+    pub fn is_warning(&self) -> bool {
+        matches!(
+            self,
+            StateValidationIssue::NoOutgoingEdges { .. }
+                | StateValidationIssue::TransientClass { .. }
+                | StateValidationIssue::PeriodicClass { .. }
+                | StateValidationIssue::MultipleClosedClasses { .. }
+        )
+    }
 }
 
 impl std::fmt::Display for StateValidationIssue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             StateValidationIssue::NoOutgoingEdges { name, .. } => {
-                write!(f, "{} has no outgoing edges", name)
+                write!(f, "{} has no outgoing edges and is absorbing", name)
             }
             StateValidationIssue::NoIncomingEdges { name, .. } => {
                 write!(f, "{} has no incoming edges", name)
             }
+            StateValidationIssue::TransientClass { names, .. } => {
+                write!(f, "Transient class {{{}}}", names.join(", "))
+            }
+            StateValidationIssue::PeriodicClass { names, period, .. } => {
+                write!(
+                    f,
+                    "Periodic class {{{}}} with period {}",
+                    names.join(", "),
+                    period
+                )
+            }
+            StateValidationIssue::MultipleClosedClasses { count, .. } => {
+                write!(
+                    f,
+                    "{} closed classes: equilibrium depends on initial weights",
+                    count
+                )
+            }
         }
     }
 }
 
+/// Whether validation issues allow computing statistics
+pub fn state_validation_passed(issues: &[StateValidationIssue]) -> bool {
+    issues.iter().all(StateValidationIssue::is_warning)
+}
+
 /// Validation issues for observable graph
 #[derive(Debug, Clone)]
 pub enum ObservableValidationIssue {
@@ -111,9 +182,367 @@ pub struct StateData {
     pub sorted_weights: Vec<f64>,
     pub weight_distribution: ProbabilityChart,
     pub equilibrium_distribution: Option<ProbabilityChart>,
+    pub equilibrium_residual: Option<f64>,
     pub entropy_rate: Option<f64>,
     pub detailed_balance_deviation: Option<f64>,
+    pub entropy_production: Option<EntropyProduction>,
     pub validation_errors: Vec<StateValidationIssue>,
+    /// Communicating class of each state, when transitions are defined
+    pub state_classes: HashMap<NodeIndex, usize>,
+    pub absorption: Option<AbsorptionTable>,
+    pub passage_times: Option<PassageTimes>,
+    pub spectral: Option<SpectralSummary>,
+    pub reversed: Option<ReversedView<StateGraphDisplay>>,
+}
+
+/// Time-reversed kernel laid out over the nodes of a graph, for display
+pub struct ReversedView<G> {
+    pub graph: G,
+    pub heatmap: HeatmapData,
+    pub sorted_weights: Vec<f64>,
+}
+
+impl<N, D> ReversedView<GraphDisplay<N, D>>
+where
+    N: Clone + HasName,
+    D: egui_graphs::DisplayNode<N, f64, petgraph::Directed, petgraph::graph::DefaultIx>,
+{
+    /// Replace the edges of `graph` by the reversed kernel, given over the
+    /// graph's own node indices
+    pub fn new(graph: &GraphDisplay<N, D>, edges: &[(NodeIndex, NodeIndex, f64)]) -> Self {
+        let graph = graph_view::with_replaced_edges(graph, edges);
+        let heatmap = compute_generic_heatmap_data(&graph);
+        let sorted_weights = collect_sorted_weights_from_display(&graph);
+        Self {
+            graph,
+            heatmap,
+            sorted_weights,
+        }
+    }
+}
+
+/// Number of eigenvalues kept for timescale comparisons
+const SPECTRAL_EIGENVALUES: usize = 4;
+
+/// Largest chain given a spectral summary. The eigen solve is dense and
+/// cubic in the number of states, and reruns on every graph edit.
+const SPECTRAL_STATE_LIMIT: usize = 400;
+
+/// Spectral quantities of a kernel, for comparing micro and macro timescales
+#[derive(Clone)]
+pub struct SpectralSummary {
+    pub slem: f64,
+    pub spectral_gap: f64,
+    pub relaxation_time: f64,
+    pub implied_timescales: Vec<f64>,
+}
+
+impl SpectralSummary {
+    /// None for chains above SPECTRAL_STATE_LIMIT states
+    pub fn new(
+        markov: &Markov<NodeIndex, NodeIndex>,
+        stationary: &Prob<NodeIndex>,
+    ) -> Option<Self> {
+        if markov.matrix.x_ix_map.len() > SPECTRAL_STATE_LIMIT {
+            return None;
+        }
+        let spectrum = markov.spectrum(SPECTRAL_EIGENVALUES, stationary);
+        Some(Self {
+            slem: spectrum.slem(),
+            spectral_gap: spectrum.spectral_gap(),
+            relaxation_time: spectrum.relaxation_time(),
+            implied_timescales: spectrum.implied_timescales(),
+        })
+    }
+}
+
+/// Block length of the macro entropy estimates. The number of observed
+/// words enumerated grows exponentially with it, and memory is measured up
+/// to order INFORMATION_BLOCK_LENGTH − 2, or less when the word budget is
+/// reached first.
+pub const INFORMATION_BLOCK_LENGTH: usize = 5;
+
+/// Largest number of forward-vector entries (observed words × states) kept
+/// while enumerating observed words. Longer blocks are skipped rather than
+/// stalling the UI on large observables.
+const INFORMATION_ENTRY_BUDGET: usize = 2_000_000;
+
+/// Information measures of a process, in nats
+#[derive(Clone)]
+pub struct InformationMeasures {
+    pub entropy: f64,
+    pub entropy_rate: f64,
+    pub mutual_information: f64,
+    pub excess_entropy: f64,
+}
+
+impl InformationMeasures {
+    fn from_blocks(blocks: &BlockEntropies) -> Self {
+        Self {
+            entropy: blocks.entropies.first().copied().unwrap_or(0.0),
+            entropy_rate: blocks.entropy_rate().unwrap_or(0.0),
+            mutual_information: blocks.mutual_information().unwrap_or(0.0),
+            excess_entropy: blocks.excess_entropy().unwrap_or(0.0),
+        }
+    }
+}
+
+/// Information measures of the micro chain and of the observed process it
+/// induces, both at the micro equilibrium
+#[derive(Clone)]
+pub struct InformationComparison {
+    pub micro: InformationMeasures,
+    pub observed: InformationMeasures,
+    /// Longest observed block that fit in the word budget
+    pub observed_block_length: usize,
+    /// I(Y_{t+1}; Y_{t−k} | Y_{t−k+1}, …, Y_t) of the observed process,
+    /// for orders k = 1, 2, …
+    pub observed_memory: Vec<f64>,
+}
+
+impl InformationComparison {
+    pub fn new(
+        state_markov: &Markov<NodeIndex, NodeIndex>,
+        observable_markov: &Markov<NodeIndex, NodeIndex>,
+        stationary: &Prob<NodeIndex>,
+    ) -> Self {
+        let micro = state_markov.block_entropies(stationary, INFORMATION_BLOCK_LENGTH);
+        let states = state_markov.matrix.x_ix_map.len().max(1);
+        let observed = state_markov.observed_block_entropies_bounded(
+            observable_markov,
+            stationary,
+            INFORMATION_BLOCK_LENGTH,
+            INFORMATION_ENTRY_BUDGET / states,
+        );
+        Self {
+            micro: InformationMeasures::from_blocks(&micro),
+            observed: InformationMeasures::from_blocks(&observed),
+            observed_block_length: observed.entropies.len(),
+            observed_memory: observed.conditional_mutual_informations(),
+        }
+    }
+}
+
+/// Effective information of the micro chain and of the observed chain, each
+/// under a uniform intervention over its own states
+#[derive(Clone)]
+pub struct CausalEmergence {
+    pub micro: EffectiveInformation,
+    pub observed: EffectiveInformation,
+}
+
+impl CausalEmergence {
+    /// None when either kernel has targets without a row, such as observed
+    /// values without mass
+    pub fn new(
+        state_markov: &Markov<NodeIndex, NodeIndex>,
+        observed_markov: &Markov<NodeIndex, NodeIndex>,
+    ) -> Option<Self> {
+        Some(Self {
+            micro: state_markov.effective_information().ok()?,
+            observed: observed_markov.effective_information().ok()?,
+        })
+    }
+
+    /// EI(macro) − EI(micro), positive when the coarse description is the
+    /// more informative one
+    pub fn emergence(&self) -> f64 {
+        self.observed.effective_information - self.micro.effective_information
+    }
+}
+
+/// Largest block deviation still counted as lumpable
+const LUMPABILITY_TOLERANCE: f64 = 1e-9;
+
+/// Number of micro states listed as breaking lumpability
+const LUMPABILITY_WORST_STATES: usize = 5;
+
+/// Lumpability of the observable with respect to the state chain
+#[derive(Clone)]
+pub struct LumpabilityReport {
+    pub strongly_lumpable: bool,
+    /// Sufficient condition: the reversed chain is strongly lumpable
+    pub weakly_lumpable: bool,
+    pub intertwining_error: f64,
+    pub max_block_deviation: f64,
+    /// Micro states with the largest row deviation, worst first
+    pub worst_states: Vec<(NodeIndex, String, f64)>,
+}
+
+impl LumpabilityReport {
+    pub fn new(
+        state_markov: &Markov<NodeIndex, NodeIndex>,
+        observable_markov: &Markov<NodeIndex, NodeIndex>,
+        stationary: &Prob<NodeIndex>,
+        state_graph: &StateGraphDisplay,
+    ) -> Option<Self> {
+        let lumpability = state_markov
+            .lumpability(observable_markov, stationary)
+            .ok()?;
+        let weakly_lumpable = state_markov
+            .reverse_lumpability(observable_markov, stationary)
+            .is_ok_and(|reverse| reverse.is_strongly_lumpable(LUMPABILITY_TOLERANCE));
+
+        let worst_states = lumpability
+            .worst_states()
+            .into_iter()
+            .filter(|(_, deviation)| *deviation > LUMPABILITY_TOLERANCE)
+            .take(LUMPABILITY_WORST_STATES)
+            .map(|(idx, deviation)| {
+                let name = state_graph
+                    .node(idx)
+                    .map(|n| n.payload().name.clone())
+                    .unwrap_or_else(|| format!("Node {}", idx.index()));
+                (idx, name, deviation)
+            })
+            .collect();
+
+        let strongly_lumpable = lumpability.is_strongly_lumpable(LUMPABILITY_TOLERANCE);
+        Some(Self {
+            strongly_lumpable,
+            weakly_lumpable: strongly_lumpable || weakly_lumpable,
+            intertwining_error: lumpability.intertwining_error,
+            max_block_deviation: lumpability.max_block_deviation(),
+            worst_states,
+        })
+    }
+}
+
+/// Mean first-passage times of the state chain, labelled for display
+#[derive(Clone)]
+pub struct PassageTimes {
+    pub kemeny_constant: f64,
+    pub return_times: HashMap<NodeIndex, f64>,
+    /// Mean time to reach every other state, sorted by target name
+    pub first_passage: HashMap<NodeIndex, Vec<(String, f64)>>,
+}
+
+impl PassageTimes {
+    pub fn new(hitting: &HittingTimes<NodeIndex>, labels: &HashMap<NodeIndex, String>) -> Self {
+        let mut first_passage: HashMap<NodeIndex, Vec<(String, f64)>> = HashMap::new();
+        for (from, to, time) in hitting
+            .mean_first_passage
+            .values
+            .iter()
+            .filter(|(_, (i, j))| i != j)
+            .filter_map(|(&time, (i, j))| {
+                let from = *hitting.mean_first_passage.x_ix_map.value_of(i)?;
+                let to = *hitting.mean_first_passage.y_ix_map.value_of(j)?;
+                Some((from, to, time))
+            })
+        {
+            let name = labels
+                .get(&to)
+                .cloned()
+                .unwrap_or_else(|| format!("Node {}", to.index()));
+            first_passage.entry(from).or_default().push((name, time));
+        }
+        for times in first_passage.values_mut() {
+            times.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+
+        Self {
+            kemeny_constant: hitting.kemeny_constant,
+            return_times: hitting.mean_return_times.enumerate().collect(),
+            first_passage,
+        }
+    }
+}
+
+/// Absorption statistics of the state chain, labelled for display
+#[derive(Clone)]
+pub struct AbsorptionTable {
+    pub absorbing: Vec<String>,
+    pub rows: Vec<AbsorptionRow>,
+}
+
+/// Absorption statistics starting from one transient state
+#[derive(Clone)]
+pub struct AbsorptionRow {
+    pub name: String,
+    pub expected_steps: f64,
+    pub steps_std: f64,
+    /// Probability of absorption in each state of `AbsorptionTable::absorbing`
+    pub probabilities: Vec<f64>,
+}
+
+impl AbsorptionTable {
+    pub fn new(absorption: &Absorption<NodeIndex>, labels: &HashMap<NodeIndex, String>) -> Self {
+        let name = |idx: &NodeIndex| {
+            labels
+                .get(idx)
+                .cloned()
+                .unwrap_or_else(|| format!("Node {}", idx.index()))
+        };
+
+        let mut rows: Vec<AbsorptionRow> = absorption
+            .transient
+            .iter()
+            .map(|idx| AbsorptionRow {
+                name: name(idx),
+                expected_steps: absorption.expected_steps.get(idx).unwrap_or(0.0),
+                steps_std: absorption.steps_variance.get(idx).unwrap_or(0.0).sqrt(),
+                probabilities: absorption
+                    .absorbing
+                    .iter()
+                    .map(|a| absorption.probabilities.get(idx, a).unwrap_or(0.0))
+                    .collect(),
+            })
+            .collect();
+        rows.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            absorbing: absorption.absorbing.iter().map(name).collect(),
+            rows,
+        }
+    }
+}
+
+/// Posterior over states given each observable value, labelled for display
+#[derive(Clone)]
+pub struct Retrodiction {
+    /// Under the initial state weights, sorted by state name
+    pub initial: HashMap<NodeIndex, Vec<(String, f64)>>,
+    /// Under the state equilibrium, sorted by state name
+    pub equilibrium: HashMap<NodeIndex, Vec<(String, f64)>>,
+}
+
+impl Retrodiction {
+    pub fn new(
+        observable: &Markov<NodeIndex, NodeIndex>,
+        initial: &Prob<NodeIndex>,
+        equilibrium: &Prob<NodeIndex>,
+        labels: &HashMap<NodeIndex, String>,
+    ) -> Self {
+        Self {
+            initial: Self::posteriors(observable, initial, labels),
+            equilibrium: Self::posteriors(observable, equilibrium, labels),
+        }
+    }
+
+    // Rows of the Bayesian inverse; values the prior cannot produce are absent
+    fn posteriors(
+        observable: &Markov<NodeIndex, NodeIndex>,
+        prior: &Prob<NodeIndex>,
+        labels: &HashMap<NodeIndex, String>,
+    ) -> HashMap<NodeIndex, Vec<(String, f64)>> {
+        let Ok(inverse) = observable.bayesian_inverse(prior) else {
+            return HashMap::new();
+        };
+
+        let mut posteriors: HashMap<NodeIndex, Vec<(String, f64)>> = HashMap::new();
+        for (value, state, p) in inverse.enumerate().filter(|(_, _, p)| *p > 0.0) {
+            let name = labels
+                .get(&state)
+                .cloned()
+                .unwrap_or_else(|| format!("Node {}", state.index()));
+            posteriors.entry(value).or_default().push((name, p));
+        }
+        for states in posteriors.values_mut() {
+            states.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+        posteriors
+    }
 }
 
 /// Combined observable data that is calculated together to ensure consistency
@@ -130,10 +559,120 @@ pub struct ObservedData {
     pub heatmap: HeatmapData,
     pub sorted_weights: Vec<f64>,
     pub weight_distribution: ProbabilityChart,
-    pub equilibrium_from_state: Option<ProbabilityChart>,
-    pub equilibrium_calculated: Option<ProbabilityChart>,
-    pub entropy_rate: Option<f64>,
-    pub detailed_balance_deviation: Option<f64>,
+    /// None until both graphs pass validation
+    pub analyses: Option<ObservedAnalyses>,
+}
+
+/// Analyses of the observed process
+pub struct ObservedAnalyses {
+    pub equilibrium_from_state: ProbabilityChart,
+    /// Equilibrium of the observed kernel, or the one pushed from the state
+    /// equilibrium when the kernel cannot be built
+    pub equilibrium_calculated: ProbabilityChart,
+    pub equilibrium_residual: Option<f64>,
+    pub entropy_rate: f64,
+    pub detailed_balance_deviation: f64,
+    pub entropy_production: Option<EntropyProduction>,
+    /// Micro entropy production seen through the observable, a lower bound
+    /// on the micro value
+    pub coarse_grained_entropy_production: EntropyProduction,
+    pub spectral: Option<SpectralSummary>,
+    pub reversed: Option<ReversedView<ObservedGraphDisplay>>,
+    pub information: InformationComparison,
+    pub lumpability: Option<LumpabilityReport>,
+    pub emergence: Option<CausalEmergence>,
+    /// Posterior over states for each observable value
+    pub retrodiction: Retrodiction,
+}
+
+/// Analyses of the observed process, None when the statistics cannot be
+/// computed
+fn compute_observed_analyses(
+    state_graph: &StateGraphDisplay,
+    observable_graph: &ObservableGraphDisplay,
+    graph: &ObservedGraphDisplay,
+    observed_labels: &HashMap<NodeIndex, String>,
+) -> Option<ObservedAnalyses> {
+    let input_stats = compute_input_statistics(state_graph, observable_graph).ok()?;
+
+    // 1. State equilibrium
+    let state_eq =
+        compute_equilibrium(&input_stats.state_markov, &input_stats.state_prob).distribution;
+
+    // 2. Observed equilibrium = state_eq · observable_markov
+    let obs_eq_from_state = state_eq.dot(&input_stats.observable_markov);
+
+    let state_labels: HashMap<NodeIndex, String> = state_graph
+        .nodes_iter()
+        .map(|(idx, node)| (idx, node.payload().name.clone()))
+        .collect();
+    let retrodiction = Retrodiction::new(
+        &input_stats.observable_markov,
+        &input_stats.state_prob,
+        &state_eq,
+        &state_labels,
+    );
+
+    let mut analyses = ObservedAnalyses {
+        equilibrium_from_state: ProbabilityChart::new(
+            obs_eq_from_state.clone(),
+            observed_labels.clone(),
+        ),
+        // Fallback to the pushed equilibrium if the observed kernel fails
+        equilibrium_calculated: ProbabilityChart::new(obs_eq_from_state, observed_labels.clone()),
+        equilibrium_residual: None,
+        entropy_rate: 0.0,
+        detailed_balance_deviation: 0.0,
+        entropy_production: None,
+        coarse_grained_entropy_production: input_stats
+            .state_markov
+            .coarse_grained_entropy_production(&input_stats.observable_markov, &state_eq),
+        spectral: None,
+        reversed: None,
+        information: InformationComparison::new(
+            &input_stats.state_markov,
+            &input_stats.observable_markov,
+            &state_eq,
+        ),
+        lumpability: LumpabilityReport::new(
+            &input_stats.state_markov,
+            &input_stats.observable_markov,
+            &state_eq,
+            state_graph,
+        ),
+        emergence: None,
+        retrodiction,
+    };
+
+    // 3. Calculated observed equilibrium and statistics
+    let Ok(output_stats) = compute_output_statistics(&input_stats) else {
+        return Some(analyses);
+    };
+    let observed_markov = &output_stats.observed_markov;
+    let stationary = compute_equilibrium(observed_markov, &output_stats.observed_prob);
+    let eq_calc = stationary.distribution;
+
+    // Observed kernel labels are observable node indices
+    let observed_idx: HashMap<NodeIndex, NodeIndex> = graph
+        .nodes_iter()
+        .map(|(idx, node)| (node.payload().observable_node_idx, idx))
+        .collect();
+    analyses.reversed = observed_markov.time_reversal(&eq_calc).ok().map(|r| {
+        let edges: Vec<_> = r
+            .enumerate()
+            .filter_map(|(x, y, p)| Some((*observed_idx.get(&x)?, *observed_idx.get(&y)?, p)))
+            .collect();
+        ReversedView::new(graph, &edges)
+    });
+
+    analyses.equilibrium_residual = Some(stationary.residual);
+    analyses.entropy_rate = observed_markov.entropy_rate(&eq_calc);
+    analyses.detailed_balance_deviation = observed_markov.detailed_balance_deviation_sum(&eq_calc);
+    analyses.entropy_production = Some(observed_markov.entropy_production_rate(&eq_calc));
+    analyses.spectral = SpectralSummary::new(observed_markov, &eq_calc);
+    analyses.emergence = CausalEmergence::new(&input_stats.state_markov, observed_markov);
+    analyses.equilibrium_calculated = ProbabilityChart::new(eq_calc, observed_labels.clone());
+    Some(analyses)
 }
 
 /// Validate state graph for connectivity issues
@@ -166,9 +705,57 @@ pub fn validate_state_graph(
         }
     }
 
+    // Class structure is only meaningful once the chain is well defined
+    if state_validation_passed(&errors)
+        && let Ok(markov) = build_state_markov(graph)
+    {
+        errors.extend(class_issues(graph, &markov.communicating_classes()));
+    }
+
     errors
 }
 
+fn class_issues(
+    graph: &crate::graph_view::StateGraphDisplay,
+    classes: &ClassDecomposition<NodeIndex>,
+) -> Vec<StateValidationIssue> {
+    let names = |states: &[NodeIndex]| -> Vec<String> {
+        states
+            .iter()
+            .filter_map(|idx| graph.g().node_weight(*idx))
+            .map(|n| n.payload().name.clone())
+            .collect()
+    };
+
+    let mut issues = Vec::new();
+
+    for class in &classes.classes {
+        let node = class.states[0];
+        if class.is_transient() {
+            issues.push(StateValidationIssue::TransientClass {
+                node,
+                names: names(&class.states),
+            });
+        } else if class.is_periodic() {
+            issues.push(StateValidationIssue::PeriodicClass {
+                node,
+                names: names(&class.states),
+                period: class.period,
+            });
+        }
+    }
+
+    let closed: Vec<_> = classes.closed_classes().collect();
+    if closed.len() > 1 {
+        issues.push(StateValidationIssue::MultipleClosedClasses {
+            node: closed[0].states[0],
+            count: closed.len(),
+        });
+    }
+
+    issues
+}
+
 /// Validate observable graph for connectivity issues
 pub fn validate_observable_graph(
     graph: &crate::graph_view::ObservableGraphDisplay,
@@ -207,6 +794,8 @@ pub struct Cache {
     pub state_data: Memoized<Store, u64, StateData>,
     pub observable_data: Memoized<Store, u64, ObservableData>,
     pub observed_data: Memoized<Store, (u64, u64), ObservedData>,
+    /// Discounted value of each state, empty when it cannot be computed
+    pub value_function: Memoized<Store, (u64, u64), HashMap<NodeIndex, f64>>,
 }
 
 impl Cache {
@@ -219,6 +808,18 @@ impl Cache {
                 // Validate state graph
                 let validation_errors = validate_state_graph(state_graph);
 
+                let state_classes: HashMap<NodeIndex, usize> = build_state_markov(state_graph)
+                    .map(|markov| {
+                        markov
+                            .communicating_classes()
+                            .classes
+                            .iter()
+                            .enumerate()
+                            .flat_map(|(c, class)| class.states.iter().map(move |&idx| (idx, c)))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
                 let order = Order::alphabetical(state_graph);
                 let heatmap = s.state_heatmap_uncached();
                 let sorted_weights = s.state_sorted_weights_uncached();
@@ -252,31 +853,66 @@ impl Cache {
                 let weight_distribution = ProbabilityChart::new(node_stats, node_labels.clone());
 
                 // Compute equilibrium distribution and statistics for state graph only if validation passes
-                let (equilibrium, entropy_rate, detailed_balance_deviation) =
-                    if !validation_errors.is_empty() {
-                        // Validation failed - don't compute equilibrium
-                        (None, None, None)
-                    } else if s.state.graph.get().node_count() > 0 {
-                        if let Ok(input_stats) =
-                            compute_input_statistics(s.state.graph.get(), s.observable.graph.get())
-                        {
-                            let eq = input_stats.state_markov.compute_equilibrium(
-                                &input_stats.state_prob,
-                                1e-4,
-                                100,
-                            );
-                            let ent_rate = input_stats.state_markov.entropy_rate(&eq);
-                            let deviation =
-                                input_stats.state_markov.detailed_balance_deviation_sum(&eq);
-                            (Some(eq), Some(ent_rate), Some(deviation))
-                        } else {
-                            // If we can't compute stats, return None
-                            (None, None, None)
-                        }
+                let (
+                    equilibrium,
+                    equilibrium_residual,
+                    entropy_rate,
+                    detailed_balance_deviation,
+                    entropy_production,
+                    absorption,
+                    passage_times,
+                    spectral,
+                    reversed,
+                ) = if !state_validation_passed(&validation_errors) {
+                    // Validation failed - don't compute equilibrium
+                    (None, None, None, None, None, None, None, None, None)
+                } else if s.state.graph.get().node_count() > 0 {
+                    if let Ok(input_stats) =
+                        compute_input_statistics(s.state.graph.get(), s.observable.graph.get())
+                    {
+                        let stationary =
+                            compute_equilibrium(&input_stats.state_markov, &input_stats.state_prob);
+                        let eq = stationary.distribution;
+                        let ent_rate = input_stats.state_markov.entropy_rate(&eq);
+                        let deviation =
+                            input_stats.state_markov.detailed_balance_deviation_sum(&eq);
+                        let production = input_stats.state_markov.entropy_production_rate(&eq);
+                        // Only chains with absorbing states get an absorption table
+                        let absorption = input_stats
+                            .state_markov
+                            .absorption()
+                            .ok()
+                            .map(|a| AbsorptionTable::new(&a, &node_labels));
+                        // Passage times are only defined for irreducible chains
+                        let passage_times = input_stats
+                            .state_markov
+                            .hitting_times()
+                            .ok()
+                            .map(|h| PassageTimes::new(&h, &node_labels));
+                        let spectral = SpectralSummary::new(&input_stats.state_markov, &eq);
+                        let reversed = input_stats.state_markov.time_reversal(&eq).ok().map(|r| {
+                            let edges: Vec<_> = r.enumerate().collect();
+                            ReversedView::new(state_graph, &edges)
+                        });
+                        (
+                            Some(eq),
+                            Some(stationary.residual),
+                            Some(ent_rate),
+                            Some(deviation),
+                            Some(production),
+                            absorption,
+                            passage_times,
+                            spectral,
+                            reversed,
+                        )
                     } else {
-                        // Empty graph - return None
-                        (None, None, None)
-                    };
+                        // If we can't compute stats, return None
+                        (None, None, None, None, None, None, None, None, None)
+                    }
+                } else {
+                    // Empty graph - return None
+                    (None, None, None, None, None, None, None, None, None)
+                };
 
                 let equilibrium_distribution =
                     equilibrium.map(|eq| ProbabilityChart::new(eq, node_labels.clone()));
@@ -287,9 +923,16 @@ impl Cache {
                     sorted_weights,
                     weight_distribution,
                     equilibrium_distribution,
+                    equilibrium_residual,
                     entropy_rate,
                     detailed_balance_deviation,
+                    entropy_production,
                     validation_errors,
+                    state_classes,
+                    absorption,
+                    passage_times,
+                    spectral,
+                    reversed,
                 }
             },
         );
@@ -320,7 +963,7 @@ impl Cache {
                 let observable_graph = s.observable.graph.get();
 
                 // Check validation status
-                let state_valid = validate_state_graph(state_graph).is_empty();
+                let state_valid = state_validation_passed(&validate_state_graph(state_graph));
                 let observable_valid = validate_observable_graph(observable_graph).is_empty();
                 let validation_passed = state_valid && observable_valid;
 
@@ -370,92 +1013,42 @@ impl Cache {
                     ProbabilityChart::new(prob_fallback(), observed_labels.clone())
                 };
 
-                // Compute equilibrium distributions and statistics only if validation passes
-                let (
-                    equilibrium_from_state,
-                    equilibrium_calculated,
-                    entropy_rate,
-                    detailed_balance_deviation,
-                ) = if !validation_passed {
-                    // Validation failed - don't compute equilibria
-                    (None, None, None, None)
-                } else if state_graph.node_count() > 0 {
-                    match compute_input_statistics(s.state.graph.get(), s.observable.graph.get()) {
-                        Ok(input_stats) => {
-                            // 1. State equilibrium
-                            let state_eq = input_stats.state_markov.compute_equilibrium(
-                                &input_stats.state_prob,
-                                1e-4,
-                                100,
-                            );
-
-                            // 2. Observed equilibrium = state_eq · observable_markov
-                            let obs_eq_from_state = state_eq.dot(&input_stats.observable_markov);
-
-                            // 3. Calculated observed equilibrium and statistics
-                            let (obs_eq_calculated, ent_rate, deviation) =
-                                match compute_output_statistics(&input_stats) {
-                                    Ok(output_stats) => {
-                                        let eq_calc =
-                                            output_stats.observed_markov.compute_equilibrium(
-                                                &output_stats.observed_prob,
-                                                1e-4,
-                                                100,
-                                            );
-                                        let ent_r =
-                                            output_stats.observed_markov.entropy_rate(&eq_calc);
-                                        let dev = output_stats
-                                            .observed_markov
-                                            .detailed_balance_deviation_sum(&eq_calc);
-                                        (eq_calc, ent_r, dev)
-                                    }
-                                    Err(_) => {
-                                        // Fallback to observed_prob if calculation fails
-                                        (obs_eq_from_state.clone(), 0.0, 0.0)
-                                    }
-                                };
-
-                            (
-                                Some(obs_eq_from_state),
-                                Some(obs_eq_calculated),
-                                Some(ent_rate),
-                                Some(deviation),
-                            )
-                        }
-                        Err(_) => {
-                            // Computation failed - return None
-                            (None, None, None, None)
-                        }
-                    }
+                // Analyses only when validation passes
+                let analyses = if validation_passed && state_graph.node_count() > 0 {
+                    compute_observed_analyses(
+                        state_graph,
+                        observable_graph,
+                        &graph,
+                        &observed_labels,
+                    )
                 } else {
-                    // Empty graph - return None
-                    (None, None, None, None)
+                    None
                 };
 
-                let equilibrium_from_state = equilibrium_from_state
-                    .map(|eq| ProbabilityChart::new(eq, observed_labels.clone()));
-
-                let equilibrium_calculated = equilibrium_calculated
-                    .map(|eq| ProbabilityChart::new(eq, observed_labels.clone()));
-
                 ObservedData {
                     order,
                     graph,
                     heatmap,
                     sorted_weights: weights,
                     weight_distribution,
-                    equilibrium_from_state,
-                    equilibrium_calculated,
-                    entropy_rate,
-                    detailed_balance_deviation,
+                    analyses,
                 }
             },
         );
 
+        let value_function = Memoized::new(
+            |s: &Store| (s.state.graph.version(), s.state.discount.version()),
+            |s: &Store| {
+                compute_value_function(s.state.graph.get(), *s.state.discount.get())
+                    .unwrap_or_default()
+            },
+        );
+
         Self {
             state_data,
             observable_data,
             observed_data,
+            value_function,
         }
     }
 }
@@ -50,6 +50,7 @@ pub fn run(store: &mut Store, effect: Effect) {
                     .graph
                     .set(setup_observable_graph_display(&observable_graph_raw));
                 store.layout_settings = state.layout_settings;
+                store.sequence_posterior = None;
                 Ok(())
             })();
             if let Err(e) = result {
@@ -3,7 +3,7 @@
 use crate::graph_view::{
     ObservableGraphDisplay, ObservedGraphDisplay, StateGraphDisplay, setup_observed_graph_display,
 };
-use markov::{Markov, Matrix, Prob, Vector};
+use markov::{Hmm, Markov, Matrix, Prob, Stationary, Vector};
 use ndarray::linalg::Dot;
 use petgraph::stable_graph::NodeIndex;
 use petgraph::stable_graph::StableGraph;
@@ -20,6 +20,8 @@ pub trait HasName {
 pub struct StateNode {
     pub name: String,
     pub weight: f64,
+    /// Reward (or, when negative, cost) collected on each step spent here
+    pub reward: Option<f64>,
 }
 
 impl HasName for StateNode {
@@ -37,14 +39,17 @@ pub fn default_state_graph() -> StateGraph {
     let s_1 = g.add_node(StateNode {
         name: make_name(1),
         weight: 1.0,
+        reward: None,
     });
     let s_2 = g.add_node(StateNode {
         name: make_name(2),
         weight: 3.0,
+        reward: None,
     });
     let s_3 = g.add_node(StateNode {
         name: make_name(3),
         weight: 2.0,
+        reward: None,
     });
 
     g.add_edge(s_1, s_2, 1.0);
@@ -268,6 +273,27 @@ where
 // Weight Computation
 // ------------------------------------------------------------------
 
+const EQUILIBRIUM_TOLERANCE: f64 = 1e-10;
+const EQUILIBRIUM_MAX_ITERATIONS: usize = 10_000;
+
+/// Equilibrium of a chain: direct solve when the stationary distribution
+/// is unique, otherwise lazy power iteration from `initial`.
+pub fn compute_equilibrium(
+    markov: &Markov<NodeIndex, NodeIndex>,
+    initial: &Prob<NodeIndex>,
+) -> Stationary<NodeIndex> {
+    markov
+        .stationary_direct(EQUILIBRIUM_TOLERANCE)
+        .unwrap_or_else(|_| {
+            markov.stationary_lazy_power(
+                initial,
+                0.5,
+                EQUILIBRIUM_TOLERANCE,
+                EQUILIBRIUM_MAX_ITERATIONS,
+            )
+        })
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum StatisticsError {
     #[error("state graph is empty")]
@@ -276,6 +302,166 @@ pub enum StatisticsError {
     ProbError(#[from] markov::prob::BuildError),
     #[error("markov construction failed: {0}")]
     MarkovError(#[from] markov::markov::BuildError),
+    #[error("coarse-graining failed: {0}")]
+    CoarseningError(#[from] markov::CoarseningError),
+    #[error("decoding failed: {0}")]
+    HmmError(#[from] markov::HmmError),
+    #[error("value function failed: {0}")]
+    RewardError(#[from] markov::RewardError),
+    #[error("unknown observable value: {0}")]
+    UnknownObservableValue(String),
+}
+
+/// Build the state transition kernel from the state graph edges.
+/// States without outgoing edges are absorbing.
+pub fn build_state_markov(
+    state_graph: &StateGraphDisplay,
+) -> Result<Markov<NodeIndex, NodeIndex>, StatisticsError> {
+    let state_edges: Vec<(NodeIndex, NodeIndex, f64)> = state_graph
+        .g()
+        .edge_references()
+        .map(|e| (e.source(), e.target(), (*e.weight().payload())))
+        .collect();
+
+    Ok(Markov::from_matrix_with_sinks(Matrix::from_assoc(
+        state_edges,
+    ))?)
+}
+
+/// Discounted value of the node rewards, the solution of (I − γP)v = r.
+/// Nodes without a reward earn nothing.
+pub fn compute_value_function(
+    state_graph: &StateGraphDisplay,
+    gamma: f64,
+) -> Result<std::collections::HashMap<NodeIndex, f64>, StatisticsError> {
+    if state_graph.node_count() == 0 {
+        return Err(StatisticsError::EmptyStateGraph);
+    }
+
+    let rewards: Vec<(NodeIndex, f64)> = state_graph
+        .nodes_iter()
+        .filter_map(|(idx, node)| node.payload().reward.map(|r| (idx, r)))
+        .collect();
+
+    let value = build_state_markov(state_graph)?
+        .with_rewards(Some(&Vector::from_assoc(rewards)), None)
+        .discounted(gamma)?;
+    Ok(value.enumerate().collect())
+}
+
+/// Observable grouping the states into `macrostates` metastable sets by
+/// PCCA+, as (state, macrostate, weight) triples. Fuzzy memberships split
+/// a state between several macrostates; otherwise each state goes to one.
+pub fn suggest_observable(
+    state_graph: &StateGraphDisplay,
+    macrostates: usize,
+    fuzzy: bool,
+) -> Result<Vec<(NodeIndex, usize, f64)>, StatisticsError> {
+    if state_graph.node_count() == 0 {
+        return Err(StatisticsError::EmptyStateGraph);
+    }
+
+    let state_weights: Vec<(NodeIndex, f64)> = state_graph
+        .nodes_iter()
+        .map(|(idx, node)| (idx, node.payload().weight))
+        .collect();
+    let state_prob = Prob::from_vector(Vector::from_assoc(state_weights))?;
+    let state_markov = build_state_markov(state_graph)?;
+    let stationary = compute_equilibrium(&state_markov, &state_prob).distribution;
+
+    let coarsening = state_markov.pcca(macrostates, &stationary)?;
+    let kernel = if fuzzy {
+        coarsening.memberships
+    } else {
+        coarsening.partition
+    };
+    Ok(kernel.enumerate().collect())
+}
+
+/// Micro states inferred from an observed sequence of observable values
+#[derive(Clone)]
+pub struct SequencePosterior {
+    /// Observed values, one per time step
+    pub observations: Vec<String>,
+    /// Micro state names, in the order of the rows of `posterior`
+    pub states: Vec<String>,
+    /// P(X_t = x | y_1..y_T), one row per state and one entry per step
+    pub posterior: Vec<Vec<f64>>,
+    /// Most likely micro path
+    pub viterbi: Vec<String>,
+    pub log_likelihood: f64,
+}
+
+/// Decode comma or newline separated observable value names with the
+/// state chain as hidden dynamics, started at its equilibrium, and the
+/// observable as emissions.
+pub fn decode_observed_sequence(
+    state_graph: &StateGraphDisplay,
+    observable_graph: &ObservableGraphDisplay,
+    text: &str,
+) -> Result<SequencePosterior, StatisticsError> {
+    let names: Vec<&str> = text
+        .split([',', '\n'])
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect();
+    let observations = names
+        .iter()
+        .map(|name| {
+            observable_graph
+                .nodes_iter()
+                .find(|(_, node)| {
+                    node.payload().node_type == ObservableNodeType::Destination
+                        && node.payload().name == *name
+                })
+                .map(|(idx, _)| idx)
+                .ok_or_else(|| StatisticsError::UnknownObservableValue(name.to_string()))
+        })
+        .collect::<Result<Vec<NodeIndex>, _>>()?;
+
+    let input_stats = compute_input_statistics(state_graph, observable_graph)?;
+    let stationary =
+        compute_equilibrium(&input_stats.state_markov, &input_stats.state_prob).distribution;
+    let hmm = Hmm::new(
+        input_stats.state_markov,
+        input_stats.observable_markov,
+        stationary,
+    )?;
+
+    let smoothing = hmm.smooth(&observations)?;
+    let viterbi = hmm.viterbi(&observations)?;
+
+    let state_name = |idx: &NodeIndex| {
+        state_graph
+            .node(*idx)
+            .map(|node| node.payload().name.clone())
+            .unwrap_or_else(|| format!("Node {}", idx.index()))
+    };
+    let states: Vec<NodeIndex> = hmm
+        .transition
+        .matrix
+        .x_ix_map
+        .iter()
+        .map(|(_, idx)| *idx)
+        .collect();
+    let posterior = states
+        .iter()
+        .map(|idx| {
+            smoothing
+                .smoothed
+                .iter()
+                .map(|p| p.prob(idx).unwrap_or(0.0))
+                .collect()
+        })
+        .collect();
+
+    Ok(SequencePosterior {
+        observations: names.iter().map(|name| name.to_string()).collect(),
+        states: states.iter().map(state_name).collect(),
+        posterior,
+        viterbi: viterbi.states.iter().map(state_name).collect(),
+        log_likelihood: smoothing.log_likelihood,
+    })
 }
 
 #[derive(Clone)]
@@ -303,13 +489,7 @@ pub fn compute_input_statistics(
     let state_prob = Prob::from_vector(Vector::from_assoc(state_weights))?;
 
     // 3. Build state_markov from state graph edges (all nodes)
-    let state_g = state_graph.g();
-    let state_edges: Vec<(NodeIndex, NodeIndex, f64)> = state_g
-        .edge_references()
-        .map(|e| (e.source(), e.target(), (*e.weight().payload())))
-        .collect();
-
-    let state_markov = Markov::from_matrix(Matrix::from_assoc(state_edges))?;
+    let state_markov = build_state_markov(state_graph)?;
 
     // 4. Build observable_markov from observable edges (source -> destination)
     // Get edges from the underlying petgraph
@@ -354,71 +534,14 @@ pub fn compute_output_statistics(
 pub fn compute_observable_markov(
     statistics: &InputStatistics,
 ) -> Result<Markov<NodeIndex, NodeIndex>, StatisticsError> {
-    // Extract destination observable node indices (columns of observable_markov)
-    let dest_nodes: Vec<NodeIndex> = (0..statistics.observable_markov.matrix.y_ix_map.len())
-        .filter_map(|i| {
-            statistics
-                .observable_markov
-                .matrix
-                .y_ix_map
-                .value_of(i)
-                .cloned()
-        })
-        .collect();
-
-    if dest_nodes.is_empty() {
+    if statistics.observable_markov.matrix.y_ix_map.is_empty() {
         return Err(StatisticsError::EmptyStateGraph); // reuse error for now
     }
 
-    // Convert state_prob to Vector for element-wise operations
-    let p_vec = statistics.state_prob.to_vec();
-
-    // Collect all (y', y, value) triplets for the observable transition matrix
-    let mut triplets: Vec<(NodeIndex, NodeIndex, f64)> = Vec::new();
-
-    for &y in &dest_nodes {
-        // Get column F_y from observable_markov
-        let f_y = statistics
-            .observable_markov
-            .matrix
-            .get_column(&y)
-            .ok_or(StatisticsError::EmptyStateGraph)?; // Column should exist
-
-        // Compute pF_y = p ⊙ F_y (element-wise multiplication)
-        let pf_y = &p_vec * &f_y;
-
-        // Compute denominator: p · F_y
-        let denominator = p_vec.dot(&f_y);
-
-        // Skip if denominator is zero (undefined transition)
-        if denominator.abs() < 1e-10 {
-            continue;
-        }
-
-        for &y_prime in &dest_nodes {
-            // Get column F_{y'}
-            let f_y_prime = statistics
-                .observable_markov
-                .matrix
-                .get_column(&y_prime)
-                .ok_or(StatisticsError::EmptyStateGraph)?;
-
-            // Compute numerator: (pF_y) · Φ · F_{y'}
-            // First: pF_y · Φ (left multiply matrix by vector)
-            let temp_vec = pf_y.dot(&statistics.state_markov.matrix);
-
-            // Then: temp_vec · F_{y'} (dot product)
-            let numerator = temp_vec.dot(&f_y_prime);
-
-            // Compute the entry: Φ^f_{y' y}
-            let value = numerator / denominator;
-
-            triplets.push((y, y_prime, value));
-        }
-    }
-
-    // Build the observable Markov matrix from triplets
-    let observable_transition = Markov::from_matrix(Matrix::from_assoc(triplets))?;
+    // Φ^f = D⁻¹ Fᵀ diag(p) Φ F as sparse products
+    let observable_transition = statistics
+        .state_markov
+        .induced_kernel(&statistics.observable_markov, &statistics.state_prob)?;
 
     Ok(observable_transition)
 }
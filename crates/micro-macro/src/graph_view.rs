@@ -9,8 +9,9 @@ use egui_graphs::{
 };
 use once_cell::sync::Lazy;
 use petgraph::graph::DefaultIx;
-use petgraph::stable_graph::{IndexType, StableGraph};
+use petgraph::stable_graph::{IndexType, NodeIndex, StableGraph};
 use petgraph::{Directed, EdgeType};
+use std::collections::{HashMap, HashSet};
 use std::sync::RwLock;
 
 static EDGE_THICKNESS_BOUNDS: Lazy<RwLock<(f64, f64)>> = Lazy::new(|| RwLock::new((1.0, 3.0)));
@@ -380,3 +381,92 @@ where
         }
     }
 }
+
+/// Copy of `graph` with its edges replaced by `edges`, keeping node indices,
+/// payloads and locations. Used to display derived kernels over the same nodes.
+pub fn with_replaced_edges<N, D>(
+    graph: &GraphDisplay<N, D>,
+    edges: &[(NodeIndex, NodeIndex, f64)],
+) -> GraphDisplay<N, D>
+where
+    N: Clone + HasName,
+    D: DisplayNode<N, f64, Directed, DefaultIx>,
+{
+    let mut g: StableGraph<N, f64> = graph
+        .g()
+        .map(|_, node| node.payload().clone(), |_, edge| *edge.payload());
+    g.clear_edges();
+    for &(source, target, weight) in edges {
+        g.add_edge(source, target, weight);
+    }
+
+    let mut display = setup_graph_display::<N, D>(&g);
+    sync_node_locations(graph, &mut display);
+    display
+}
+
+/// Move nodes of `target` to the location of the same node in `source`
+pub fn sync_node_locations<N, D>(source: &GraphDisplay<N, D>, target: &mut GraphDisplay<N, D>)
+where
+    N: Clone,
+    D: DisplayNode<N, f64, Directed, DefaultIx>,
+{
+    let locations: Vec<_> = source
+        .nodes_iter()
+        .map(|(idx, node)| (idx, node.location()))
+        .collect();
+    for (idx, location) in locations {
+        if let Some(node) = target.node_mut(idx) {
+            node.set_location(location);
+        }
+    }
+}
+
+/// Color state nodes by group (e.g. communicating class).
+/// Nodes missing from `groups` keep the default color, and no coloring is
+/// applied when every node is in the same group.
+pub fn update_state_node_colors(graph: &mut StateGraphDisplay, groups: &HashMap<NodeIndex, usize>) {
+    let distinct = groups.values().collect::<HashSet<_>>().len();
+    let node_indices: Vec<_> = graph.nodes_iter().map(|(idx, _)| idx).collect();
+
+    for node_idx in node_indices {
+        let color = groups
+            .get(&node_idx)
+            .filter(|_| distinct > 1)
+            .map(|&group| group_color(group));
+        if let Some(node) = graph.node_mut(node_idx) {
+            node.display_mut().set_group_color(color);
+        }
+    }
+}
+
+/// Colour state nodes along the Viridis scale by value, from the lowest to
+/// the highest. Nodes without a value are left uncoloured.
+pub fn update_state_node_value_colors(
+    graph: &mut StateGraphDisplay,
+    values: &HashMap<NodeIndex, f64>,
+) {
+    let min = values.values().copied().fold(f64::INFINITY, f64::min);
+    let max = values.values().copied().fold(f64::NEG_INFINITY, f64::max);
+    let node_indices: Vec<_> = graph.nodes_iter().map(|(idx, _)| idx).collect();
+
+    for node_idx in node_indices {
+        let color = values.get(&node_idx).map(|&v| {
+            let t = if max > min {
+                (v - min) / (max - min)
+            } else {
+                0.5
+            };
+            let c = colorous::VIRIDIS.eval_continuous(t);
+            egui::Color32::from_rgb(c.r, c.g, c.b)
+        });
+        if let Some(node) = graph.node_mut(node_idx) {
+            node.display_mut().set_group_color(color);
+        }
+    }
+}
+
+fn group_color(group: usize) -> egui::Color32 {
+    let c = colorous::TABLEAU10[group % colorous::TABLEAU10.len()];
+    egui::Color32::from_rgb(c.r, c.g, c.b)
+}
@@ -69,6 +69,208 @@ pub fn create_app(cc: &eframe::CreationContext<'_>) -> State {
 
 // ------------------------------------------------------------------
 
+/// Relaxation time and implied timescales of a kernel
+fn render_spectral_summary(ui: &mut egui::Ui, spectral: &cache::SpectralSummary) {
+    ui.label(format!("SLEM: {:.4}", spectral.slem));
+    ui.label(format!("Relaxation time: {:.3}", spectral.relaxation_time));
+
+    let timescales: Vec<String> = spectral
+        .implied_timescales
+        .iter()
+        .map(|t| format!("{:.2}", t))
+        .collect();
+    if !timescales.is_empty() {
+        ui.label(format!("Timescales: {}", timescales.join(", ")));
+    }
+}
+
+/// Information measures of the micro chain and the observed process
+fn render_information_comparison(ui: &mut egui::Ui, information: &cache::InformationComparison) {
+    egui::CollapsingHeader::new("Information")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new("information_grid")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("");
+                    ui.strong("Micro");
+                    ui.strong("Macro");
+                    ui.end_row();
+
+                    let rows: [(&str, fn(&cache::InformationMeasures) -> f64); 4] = [
+                        ("Entropy", |m| m.entropy),
+                        ("Entropy rate", |m| m.entropy_rate),
+                        ("Mutual information", |m| m.mutual_information),
+                        ("Excess entropy", |m| m.excess_entropy),
+                    ];
+                    for (name, value) in rows {
+                        ui.label(name);
+                        ui.label(format!("{:.4}", value(&information.micro)));
+                        ui.label(format!("{:.4}", value(&information.observed)));
+                        ui.end_row();
+                    }
+                });
+            if information.observed_block_length < cache::INFORMATION_BLOCK_LENGTH {
+                ui.label(format!(
+                    "Macro estimates use blocks of length {} (word budget reached)",
+                    information.observed_block_length
+                ));
+            }
+        });
+}
+
+/// Effective information of the micro and observed chains and their
+/// difference, the causal emergence of the macro description
+fn render_causal_emergence(ui: &mut egui::Ui, emergence: &cache::CausalEmergence) {
+    egui::CollapsingHeader::new("Causal emergence")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new("emergence_grid")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("");
+                    ui.strong("Micro");
+                    ui.strong("Macro");
+                    ui.end_row();
+
+                    let rows: [(&str, fn(&markov::EffectiveInformation) -> f64); 4] = [
+                        ("Effective information", |e| e.effective_information),
+                        ("Determinism", |e| e.determinism),
+                        ("Degeneracy", |e| e.degeneracy),
+                        ("Effectiveness", |e| e.effectiveness),
+                    ];
+                    for (name, value) in rows {
+                        ui.label(name);
+                        ui.label(format!("{:.4}", value(&emergence.micro)));
+                        ui.label(format!("{:.4}", value(&emergence.observed)));
+                        ui.end_row();
+                    }
+                });
+            ui.label(format!("Causal emergence: {:.4}", emergence.emergence()))
+                .on_hover_text("EI(macro) − EI(micro), in nats");
+        });
+}
+
+/// Conditional mutual information of the observed process against the
+/// order of the conditioning history. Bars above zero are memory that a
+/// first-order macro kernel cannot capture.
+fn render_memory_plot(ui: &mut egui::Ui, memory: &[f64]) {
+    ui.label("Memory vs order");
+    if memory.is_empty() {
+        ui.label("No order fits in the word budget");
+        return;
+    }
+
+    let color = egui::Color32::from_rgb(150, 100, 10);
+    let bars: Vec<egui_plot::Bar> = memory
+        .iter()
+        .enumerate()
+        .map(|(k, &value)| {
+            egui_plot::Bar::new((k + 1) as f64, value)
+                .name(format!("Order {}", k + 1))
+                .width(0.6)
+                .fill(color)
+        })
+        .collect();
+
+    let chart = egui_plot::BarChart::new("observed_memory", bars)
+        .color(color)
+        .highlight(true)
+        .element_formatter(Box::new(|bar, _chart| format!("{:.4}", bar.value)));
+
+    egui_plot::Plot::new("observed_memory")
+        .height(120.0)
+        .show_axes([true, true])
+        .allow_zoom(false)
+        .allow_drag(false)
+        .allow_scroll(false)
+        .show_background(false)
+        .show_grid(false)
+        .include_y(0.0)
+        .x_axis_label("Order")
+        .y_axis_label("CMI (nats)")
+        .show(ui, |plot_ui| {
+            plot_ui.bar_chart(chart);
+        });
+}
+
+/// Posterior probability of each micro state along a decoded sequence
+fn render_sequence_posterior(ui: &mut egui::Ui, posterior: &graph_state::SequencePosterior) {
+    ui.label(format!("Log-likelihood: {:.4}", posterior.log_likelihood));
+    ui.label(format!(
+        "Most likely path: {}",
+        posterior.viterbi.join(" → ")
+    ));
+
+    let observations = posterior.observations.clone();
+    egui_plot::Plot::new("sequence_posterior")
+        .height(140.0)
+        .legend(egui_plot::Legend::default())
+        .allow_zoom(false)
+        .allow_drag(false)
+        .allow_scroll(false)
+        .show_background(false)
+        .include_y(0.0)
+        .include_y(1.0)
+        .x_axis_formatter(move |val, _range| {
+            let t = val.value;
+            if t >= 0.0 && t.fract() == 0.0 {
+                observations.get(t as usize).cloned().unwrap_or_default()
+            } else {
+                String::new()
+            }
+        })
+        .y_axis_label("Posterior")
+        .show(ui, |plot_ui| {
+            for (name, row) in posterior.states.iter().zip(&posterior.posterior) {
+                let points: egui_plot::PlotPoints = row
+                    .iter()
+                    .enumerate()
+                    .map(|(t, &p)| [t as f64, p])
+                    .collect();
+                plot_ui.line(egui_plot::Line::new(name.clone(), points));
+            }
+        });
+}
+
+/// Entropy production rate, or ∞ with the number of one-way transitions
+fn format_entropy_production(production: &markov::EntropyProduction) -> String {
+    if production.is_finite() {
+        format!("{:.4}", production.rate)
+    } else {
+        format!("∞ ({} one-way)", production.one_way_transitions)
+    }
+}
+
+/// Absorption probabilities and times for each transient state
+fn render_absorption_panel(ui: &mut egui::Ui, absorption: &cache::AbsorptionTable) {
+    egui::CollapsingHeader::new("Absorption")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::ScrollArea::horizontal().show(ui, |ui| {
+                egui::Grid::new("absorption_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("From");
+                        ui.strong("Steps");
+                        for name in &absorption.absorbing {
+                            ui.strong(format!("→ {}", name));
+                        }
+                        ui.end_row();
+
+                        for row in &absorption.rows {
+                            ui.label(&row.name);
+                            ui.label(format!("{:.2} ± {:.2}", row.expected_steps, row.steps_std));
+                            for p in &row.probabilities {
+                                ui.label(format!("{:.3}", p));
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+}
+
 /// Collect all edge weights from a graph and return them sorted (including duplicates)
 /// Always prepends 0.0 to ensure the smallest actual weight doesn't map to minimum thickness
 /// Create probability bars from raw data, normalizing by total weight
@@ -161,6 +363,125 @@ fn render_probability_chart(
 type NodeConnections = (Vec<(String, f64)>, Vec<(String, f64)>);
 
 impl State {
+    fn render_lumpability_panel(&mut self, ui: &mut egui::Ui, report: &cache::LumpabilityReport) {
+        egui::CollapsingHeader::new("Lumpability")
+            .default_open(true)
+            .show(ui, |ui| {
+                let verdict = |holds: bool, yes: &str, no: &str| {
+                    if holds {
+                        egui::RichText::new(yes.to_string())
+                            .color(egui::Color32::from_rgb(40, 130, 60))
+                    } else {
+                        egui::RichText::new(no.to_string())
+                            .color(egui::Color32::from_rgb(170, 30, 30))
+                    }
+                };
+                ui.horizontal(|ui| {
+                    ui.label("Strongly lumpable:");
+                    ui.label(verdict(report.strongly_lumpable, "yes", "no"));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Weakly lumpable at equilibrium:");
+                    ui.label(verdict(report.weakly_lumpable, "yes", "not established"));
+                });
+                ui.label(format!(
+                    "Intertwining error: {:.2e}",
+                    report.intertwining_error
+                ));
+                ui.label(format!(
+                    "Max block deviation: {:.2e}",
+                    report.max_block_deviation
+                ));
+
+                if report.worst_states.is_empty() {
+                    return;
+                }
+                ui.add_space(4.0);
+                ui.label("States breaking lumpability:");
+                for (node_idx, name, deviation) in &report.worst_states {
+                    let text = egui::RichText::new(format!("• {}: {:.3}", name, deviation))
+                        .color(egui::Color32::from_rgb(170, 30, 30));
+                    let button = egui::Button::new(text)
+                        .fill(egui::Color32::TRANSPARENT)
+                        .frame(false);
+
+                    if ui.add(button).clicked() {
+                        self.dispatch(actions::Action::SelectStateNode {
+                            node_idx: *node_idx,
+                            selected: true,
+                        });
+                        self.dispatch(actions::Action::SetActiveTab {
+                            tab: store::ActiveTab::DynamicalSystem,
+                        });
+                    }
+                }
+            });
+    }
+
+    fn render_sequence_decoder(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Decode sequence")
+            .default_open(false)
+            .show(ui, |ui| {
+                let mut text = self.store.observed_sequence_input.clone();
+                let response = ui.add(
+                    egui::TextEdit::multiline(&mut text)
+                        .hint_text("Comma-separated values, e.g. Value 0, Value 1")
+                        .desired_rows(2)
+                        .desired_width(f32::INFINITY),
+                );
+                if response.changed() {
+                    self.dispatch(actions::Action::SetObservedSequenceInput { text });
+                }
+                if ui.button("Decode").clicked() {
+                    self.dispatch(actions::Action::DecodeObservedSequence);
+                }
+
+                if let Some(posterior) = &self.store.sequence_posterior {
+                    render_sequence_posterior(ui, posterior);
+                }
+            });
+    }
+
+    fn render_suggest_observable_dialog(&mut self, ctx: &egui::Context) {
+        let Some(mut dialog) = self.store.suggest_observable else {
+            return;
+        };
+        let state_count = self.store.state.graph.get().node_count().max(1);
+        let mut apply = false;
+        let mut cancel = false;
+
+        egui::Window::new("Suggest observable")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    "Group states into metastable sets using the leading eigenvectors (PCCA+).",
+                );
+                ui.label("This replaces the current observable values.");
+                ui.add_space(4.0);
+                ui.add(egui::Slider::new(&mut dialog.macrostates, 1..=state_count).text("Values"));
+                ui.checkbox(&mut dialog.fuzzy, "Fuzzy memberships");
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    apply = ui.button("Apply").clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+
+        if apply {
+            self.dispatch(actions::Action::ApplySuggestedObservable {
+                macrostates: dialog.macrostates,
+                fuzzy: dialog.fuzzy,
+            });
+        } else if cancel {
+            self.dispatch(actions::Action::SetSuggestObservableDialog { dialog: None });
+        } else if self.store.suggest_observable != Some(dialog) {
+            self.dispatch(actions::Action::SetSuggestObservableDialog {
+                dialog: Some(dialog),
+            });
+        }
+    }
+
     fn render_state_validation_panel(
         &mut self,
         ui: &mut egui::Ui,
@@ -170,22 +491,36 @@ impl State {
             return;
         }
 
+        // Structural warnings alone get an amber frame
+        let (fill, stroke) = if cache::state_validation_passed(errors) {
+            (
+                egui::Color32::from_rgb(255, 245, 220),
+                egui::Color32::from_rgb(200, 150, 40),
+            )
+        } else {
+            (
+                egui::Color32::from_rgb(255, 230, 230),
+                egui::Color32::from_rgb(200, 60, 60),
+            )
+        };
+
         egui::Frame::new()
-            .fill(egui::Color32::from_rgb(255, 230, 230))
-            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(200, 60, 60)))
+            .fill(fill)
+            .stroke(egui::Stroke::new(1.0, stroke))
             .inner_margin(egui::Margin::symmetric(8, 8))
             .show(ui, |ui| {
                 ui.vertical(|ui| {
                     ui.strong("State Graph Validation Issues");
                     ui.add_space(4.0);
                     for error in errors {
-                        let node_idx = match error {
-                            cache::StateValidationIssue::NoOutgoingEdges { node, .. } => *node,
-                            cache::StateValidationIssue::NoIncomingEdges { node, .. } => *node,
-                        };
+                        let node_idx = error.node();
 
-                        let text = egui::RichText::new(format!("• {}", error))
-                            .color(egui::Color32::from_rgb(170, 30, 30));
+                        let color = if error.is_warning() {
+                            egui::Color32::from_rgb(150, 100, 10)
+                        } else {
+                            egui::Color32::from_rgb(170, 30, 30)
+                        };
+                        let text = egui::RichText::new(format!("• {}", error)).color(color);
 
                         let button = egui::Button::new(text)
                             .fill(egui::Color32::TRANSPARENT)
@@ -649,6 +984,11 @@ impl State {
                             &validation_errors,
                         );
                         ui.add_space(6.0);
+                        let absorption = self.cache.state_data.get(&self.store).absorption.clone();
+                        if let Some(absorption) = absorption {
+                            render_absorption_panel(ui, &absorption);
+                            ui.add_space(6.0);
+                        }
                         self.layout_settings_panel(
                             ui,
                             ActiveTab::DynamicalSystem,
@@ -746,6 +1086,7 @@ impl State {
 
                         // Weight editor
                         self.weight_editor(ui, node_idx);
+                        self.reward_editor(ui, node_idx);
 
                         // Only show connection info if this node is selected
                         if is_selected {
@@ -755,6 +1096,12 @@ impl State {
                                     node_idx,
                                 );
                             Self::connections_widget(ui, incoming, outgoing);
+
+                            let passage_times =
+                                self.cache.state_data.get(&self.store).passage_times.clone();
+                            if let Some(passage_times) = passage_times {
+                                Self::passage_times_widget(ui, &passage_times, node_idx);
+                            }
                         }
                     }
                 });
@@ -840,6 +1187,28 @@ impl State {
                                 ui.label("Entropy rate: N/A");
                                 ui.label("Balance dev: N/A");
                             }
+
+                            if let Some(production) = &state_data.entropy_production {
+                                ui.label(format!(
+                                    "Entropy production: {}",
+                                    format_entropy_production(production)
+                                ));
+                            }
+
+                            if let Some(residual) = state_data.equilibrium_residual {
+                                ui.label(format!("Eq. residual: {:.1e}", residual));
+                            }
+
+                            if let Some(spectral) = &state_data.spectral {
+                                render_spectral_summary(ui, spectral);
+                            }
+
+                            if let Some(passage_times) = &state_data.passage_times {
+                                ui.label(format!(
+                                    "Kemeny constant: {:.4}",
+                                    passage_times.kemeny_constant
+                                ));
+                            }
                         });
                     });
             });
@@ -855,7 +1224,16 @@ impl State {
                     .horizontal(|mut strip| {
                         // Left: Graph
                         strip.cell(|ui| {
-                            ui.heading("Graph");
+                            let show_reversed = self.store.state.show_reversed;
+                            ui.horizontal(|ui| {
+                                ui.heading("Graph");
+                                self.show_reversed_toggle(
+                                    ui,
+                                    ActiveTab::DynamicalSystem,
+                                    show_reversed,
+                                );
+                                self.value_coloring_controls(ui);
+                            });
                             ui.separator();
 
                             let tab_settings = self.store.layout_settings.dynamical_system.clone();
@@ -925,21 +1303,78 @@ impl State {
                                 .sorted_weights
                                 .clone();
                             graph_view::update_edge_thicknesses(
-                                self.store.state.graph.get_mut(),
+                                self.store.state.graph.get_mut_untracked(),
                                 sorted_weights,
                             );
 
+                            // Color nodes by value when rewards are shown, otherwise
+                            // by communicating class
+                            let show_values = self.store.state.show_values;
+                            if show_values {
+                                let values = self.cache.value_function.get(&self.store).clone();
+                                graph_view::update_state_node_value_colors(
+                                    self.store.state.graph.get_mut_untracked(),
+                                    &values,
+                                );
+                            } else {
+                                let state_classes =
+                                    self.cache.state_data.get(&self.store).state_classes.clone();
+                                graph_view::update_state_node_colors(
+                                    self.store.state.graph.get_mut_untracked(),
+                                    &state_classes,
+                                );
+                            }
+
                             let settings_interaction = self.get_settings_interaction(mode);
                             let settings_style =
                                 self.get_settings_style(tab_settings.visuals.show_labels);
                             let settings_navigation = self.get_settings_navigation();
 
+                            if show_reversed {
+                                // Reversed kernel over the same nodes, read-only
+                                let state_graph = self.store.state.graph.get();
+                                let state_data = self.cache.state_data.get_mut(&self.store);
+                                if let Some(reversed) = state_data.reversed.as_mut() {
+                                    graph_view::sync_node_locations(
+                                        state_graph,
+                                        &mut reversed.graph,
+                                    );
+                                    graph_view::update_edge_thicknesses(
+                                        &mut reversed.graph,
+                                        reversed.sorted_weights.clone(),
+                                    );
+                                    if show_values {
+                                        let values = self.cache.value_function.get(&self.store);
+                                        graph_view::update_state_node_value_colors(
+                                            &mut reversed.graph,
+                                            values,
+                                        );
+                                    } else {
+                                        graph_view::update_state_node_colors(
+                                            &mut reversed.graph,
+                                            &state_data.state_classes,
+                                        );
+                                    }
+                                    ui.add(
+                                        &mut StateGraphView::new(&mut reversed.graph)
+                                            .with_interactions(&SettingsInteraction::new())
+                                            .with_navigations(&settings_navigation)
+                                            .with_styles(&settings_style),
+                                    );
+                                } else {
+                                    ui.label("Reversed chain requires a valid state graph");
+                                }
+                                return;
+                            }
+
                             // Graph takes most of available space, leaving room for controls
                             ui.add(
-                                &mut StateGraphView::new(self.store.state.graph.get_mut())
-                                    .with_interactions(&settings_interaction)
-                                    .with_navigations(&settings_navigation)
-                                    .with_styles(&settings_style),
+                                &mut StateGraphView::new(
+                                    self.store.state.graph.get_mut_untracked(),
+                                )
+                                .with_interactions(&settings_interaction)
+                                .with_navigations(&settings_navigation)
+                                .with_styles(&settings_style),
                             );
 
                             // Edge editing functionality
@@ -972,6 +1407,23 @@ impl State {
                             ui.heading("Heatmap");
                             ui.separator();
 
+                            if self.store.state.show_reversed {
+                                let reversed_heatmap = self
+                                    .cache
+                                    .state_data
+                                    .get(&self.store)
+                                    .reversed
+                                    .as_ref()
+                                    .map(|reversed| reversed.heatmap.clone());
+                                match reversed_heatmap {
+                                    Some(heatmap) => self.show_read_only_heatmap(ui, &heatmap),
+                                    None => {
+                                        ui.label("Reversed chain requires a valid state graph");
+                                    }
+                                }
+                                return;
+                            }
+
                             // Build heatmap data
                             let (x_labels, y_labels, matrix, x_node_indices, y_node_indices) =
                                 self.cache.state_data.get(&self.store).heatmap.clone();
@@ -1070,6 +1522,12 @@ impl State {
                         self.dispatch(actions::Action::AddObservableDestinationNode { name: default_name });
                     }
 
+                    if ui.button("Suggest observable…").clicked() {
+                        self.dispatch(actions::Action::SetSuggestObservableDialog {
+                            dialog: Some(store::SuggestObservableDialog::new()),
+                        });
+                    }
+
                     // Contents - Destination node list
                     let available_height = ui.available_height() - 40.0;
                     egui::ScrollArea::vertical()
@@ -1155,6 +1613,17 @@ impl State {
                                             node_idx,
                                         );
                                     Self::connections_widget(ui, incoming, vec![]);
+
+                                    let retrodiction = self
+                                        .cache
+                                        .observed_data
+                                        .get(&self.store)
+                                        .analyses
+                                        .as_ref()
+                                        .map(|analyses| analyses.retrodiction.clone());
+                                    if let Some(retrodiction) = retrodiction {
+                                        Self::retrodiction_widget(ui, &retrodiction, node_idx);
+                                    }
                                 }
                             }
                         });
@@ -1302,7 +1771,7 @@ impl State {
                         .sorted_weights
                         .clone();
                     graph_view::update_edge_thicknesses(
-                        self.store.observable.graph.get_mut(),
+                        self.store.observable.graph.get_mut_untracked(),
                         sorted_weights,
                     );
 
@@ -1318,7 +1787,7 @@ impl State {
                         |ui| {
                             ui.add(
                                 &mut ObservableGraphView::new(
-                                    self.store.observable.graph.get_mut(),
+                                    self.store.observable.graph.get_mut_untracked(),
                                 )
                                 .with_interactions(&settings_interaction)
                                 .with_navigations(&settings_navigation)
@@ -1359,6 +1828,8 @@ impl State {
                     ui.separator();
                 });
             });
+
+        self.render_suggest_observable_dialog(ctx);
     }
 
     fn render_observed_dynamics_tab(&mut self, ctx: &egui::Context) {
@@ -1391,6 +1862,21 @@ impl State {
                             .validation_errors
                             .clone();
 
+                        let lumpability = self
+                            .cache
+                            .observed_data
+                            .get(&self.store)
+                            .analyses
+                            .as_ref()
+                            .and_then(|analyses| analyses.lumpability.clone());
+                        if let Some(report) = &lumpability {
+                            self.render_lumpability_panel(ui, report);
+                            ui.add_space(6.0);
+                        }
+
+                        self.render_sequence_decoder(ui);
+                        ui.add_space(6.0);
+
                         self.render_state_validation_panel(ui, &state_validation_errors);
                         self.render_observable_validation_panel(ui, &observable_validation_errors);
 
@@ -1476,7 +1962,18 @@ impl State {
             .exact_height(histogram_height)
             .frame(egui::Frame::side_top_panel(&ctx.style()).inner_margin(8.0))
             .show(ctx, |ui| {
+                // Micro relaxation time, for comparison with the macro one
+                let micro_relaxation_time = self
+                    .cache
+                    .state_data
+                    .get(&self.store)
+                    .spectral
+                    .as_ref()
+                    .map(|s| s.relaxation_time);
+                let micro_entropy_production =
+                    self.cache.state_data.get(&self.store).entropy_production;
                 let observed_data = self.cache.observed_data.get(&self.store);
+                let analyses = observed_data.analyses.as_ref();
                 StripBuilder::new(ui)
                     .size(Size::remainder().at_least(200.0))
                     .size(Size::remainder().at_least(200.0))
@@ -1499,15 +1996,13 @@ impl State {
 
                         strip.cell(|ui| {
                             ui.vertical(|ui| {
-                                if let Some(ref equilibrium_from_state) =
-                                    observed_data.equilibrium_from_state
-                                {
+                                if let Some(analyses) = analyses {
                                     let plot_height = ui.available_height() - 30.0;
                                     render_probability_chart(
                                         ui,
                                         "observed_equilibrium_from_state",
                                         "Observed Equilibrium",
-                                        equilibrium_from_state,
+                                        &analyses.equilibrium_from_state,
                                         observed_color,
                                         plot_height,
                                     );
@@ -1521,15 +2016,13 @@ impl State {
 
                         strip.cell(|ui| {
                             ui.vertical(|ui| {
-                                if let Some(ref equilibrium_calculated) =
-                                    observed_data.equilibrium_calculated
-                                {
+                                if let Some(analyses) = analyses {
                                     let plot_height = ui.available_height() - 30.0;
                                     render_probability_chart(
                                         ui,
                                         "observed_equilibrium_calculated",
                                         "Calculated Equilibrium",
-                                        equilibrium_calculated,
+                                        &analyses.equilibrium_calculated,
                                         observed_color,
                                         plot_height,
                                     );
@@ -1542,18 +2035,57 @@ impl State {
                         });
                         strip.cell(|ui| {
                             ui.vertical(|ui| {
-                                if let (Some(entropy_rate), Some(detailed_balance_deviation)) = (
-                                    observed_data.entropy_rate,
-                                    observed_data.detailed_balance_deviation,
-                                ) {
-                                    ui.label(format!("Entropy rate: {:.4}", entropy_rate));
-                                    ui.label(format!(
-                                        "Detailed balance deviation: {:.4}",
-                                        detailed_balance_deviation
-                                    ));
-                                } else {
+                                let Some(analyses) = analyses else {
                                     ui.label("Entropy rate: N/A");
                                     ui.label("Detailed balance deviation: N/A");
+                                    return;
+                                };
+                                ui.label(format!("Entropy rate: {:.4}", analyses.entropy_rate));
+                                ui.label(format!(
+                                    "Detailed balance deviation: {:.4}",
+                                    analyses.detailed_balance_deviation
+                                ));
+
+                                if let Some(production) = &analyses.entropy_production {
+                                    ui.label(format!(
+                                        "Entropy production: {}",
+                                        format_entropy_production(production)
+                                    ));
+                                }
+                                if let Some(micro) = micro_entropy_production {
+                                    let coarse = analyses.coarse_grained_entropy_production;
+                                    ui.label(format!(
+                                        "Micro entropy production: {}",
+                                        format_entropy_production(&micro)
+                                    ));
+                                    ui.label(format!(
+                                        "Coarse-grained bound: {}",
+                                        format_entropy_production(&coarse)
+                                    ));
+                                    if micro.is_finite() && coarse.is_finite() {
+                                        ui.label(format!(
+                                            "Hidden dissipation: {:.4}",
+                                            micro.rate - coarse.rate
+                                        ));
+                                    }
+                                }
+
+                                if let Some(residual) = analyses.equilibrium_residual {
+                                    ui.label(format!("Equilibrium residual: {:.1e}", residual));
+                                }
+
+                                if let Some(spectral) = &analyses.spectral {
+                                    render_spectral_summary(ui, spectral);
+                                    if let Some(micro) = micro_relaxation_time {
+                                        ui.label(format!("Micro relaxation time: {:.3}", micro));
+                                    }
+                                }
+
+                                render_information_comparison(ui, &analyses.information);
+                                render_memory_plot(ui, &analyses.information.observed_memory);
+
+                                if let Some(emergence) = &analyses.emergence {
+                                    render_causal_emergence(ui, emergence);
                                 }
                             });
                         });
@@ -1568,7 +2100,15 @@ impl State {
                     .size(Size::remainder())
                     .horizontal(|mut strip| {
                         strip.cell(|ui| {
-                            ui.heading("Observed Graph");
+                            let show_reversed = self.store.observed.show_reversed;
+                            ui.horizontal(|ui| {
+                                ui.heading("Observed Graph");
+                                self.show_reversed_toggle(
+                                    ui,
+                                    ActiveTab::ObservedDynamics,
+                                    show_reversed,
+                                );
+                            });
                             ui.separator();
 
                             let tab_settings = self.store.layout_settings.observed_dynamics.clone();
@@ -1641,17 +2181,43 @@ impl State {
                                 observed_data.sorted_weights.clone(),
                             );
 
+                            // Reversed kernel is drawn over the observed node layout
+                            let displayed_graph = if show_reversed {
+                                observed_data
+                                    .analyses
+                                    .as_mut()
+                                    .and_then(|analyses| analyses.reversed.as_mut())
+                                    .map(|reversed| {
+                                        graph_view::sync_node_locations(
+                                            &observed_data.graph,
+                                            &mut reversed.graph,
+                                        );
+                                        graph_view::update_edge_thicknesses(
+                                            &mut reversed.graph,
+                                            reversed.sorted_weights.clone(),
+                                        );
+                                        &mut reversed.graph
+                                    })
+                            } else {
+                                Some(&mut observed_data.graph)
+                            };
+
                             let available_height = ui.available_height() - 60.0;
                             ui.allocate_ui_with_layout(
                                 egui::Vec2::new(ui.available_width(), available_height),
                                 egui::Layout::top_down(egui::Align::Center),
-                                |ui| {
-                                    ui.add(
-                                        &mut ObservedGraphView::new(&mut observed_data.graph)
-                                            .with_interactions(&settings_interaction)
-                                            .with_navigations(&settings_navigation)
-                                            .with_styles(&settings_style),
-                                    );
+                                |ui| match displayed_graph {
+                                    Some(graph) => {
+                                        ui.add(
+                                            &mut ObservedGraphView::new(graph)
+                                                .with_interactions(&settings_interaction)
+                                                .with_navigations(&settings_navigation)
+                                                .with_styles(&settings_style),
+                                        );
+                                    }
+                                    None => {
+                                        ui.label("Reversed chain requires a valid observed graph");
+                                    }
                                 },
                             );
                         });
@@ -1660,29 +2226,22 @@ impl State {
                             ui.heading("Observed Heatmap");
                             ui.separator();
 
-                            let (x_labels, y_labels, matrix, x_node_indices, y_node_indices) =
-                                self.cache.observed_data.get(&self.store).heatmap.clone();
-
-                            let editing_state = heatmap::EditingState {
-                                editing_cell: None,
-                                edit_buffer: String::new(),
+                            let observed_data = self.cache.observed_data.get(&self.store);
+                            let heatmap = if self.store.observed.show_reversed {
+                                observed_data
+                                    .analyses
+                                    .as_ref()
+                                    .and_then(|analyses| analyses.reversed.as_ref())
+                                    .map(|reversed| reversed.heatmap.clone())
+                            } else {
+                                Some(observed_data.heatmap.clone())
                             };
 
-                            let (new_hover, _, _) = heatmap::show_heatmap(
-                                ui,
-                                &x_labels,
-                                &y_labels,
-                                &matrix,
-                                &x_node_indices,
-                                &y_node_indices,
-                                self.store.heatmap_hovered_cell,
-                                editing_state,
-                            );
-
-                            if new_hover != self.store.heatmap_hovered_cell {
-                                self.dispatch(actions::Action::SetHeatmapHoveredCell {
-                                    cell: new_hover,
-                                });
+                            match heatmap {
+                                Some(heatmap) => self.show_read_only_heatmap(ui, &heatmap),
+                                None => {
+                                    ui.label("Reversed chain requires a valid observed graph");
+                                }
                             }
                         });
                     });
@@ -1732,6 +2291,59 @@ impl State {
         });
     }
 
+    fn reward_editor(&mut self, ui: &mut egui::Ui, node_idx: NodeIndex) {
+        // Empty means no reward
+        ui.horizontal(|ui| {
+            ui.label("Reward:");
+            let current_reward = self
+                .store
+                .state
+                .graph
+                .get()
+                .node(node_idx)
+                .and_then(|n| n.payload().reward);
+            let is_focused = self.store.reward_editor.node() == Some(node_idx);
+            let mut reward_str = if is_focused {
+                self.store.reward_editor.value()
+            } else {
+                current_reward.map(|r| r.to_string()).unwrap_or_default()
+            };
+
+            let response = ui
+                .with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.add_space(ui.spacing().item_spacing.x);
+                    let text_width = ui.available_width().max(80.0);
+                    ui.add(
+                        egui::TextEdit::singleline(&mut reward_str)
+                            .desired_width(text_width)
+                            .hint_text("none"),
+                    )
+                })
+                .inner;
+            if response.gained_focus() || response.changed() {
+                self.dispatch(actions::Action::UpdateStateNodeRewardEditor {
+                    node_idx,
+                    value: reward_str,
+                });
+            };
+            if response.lost_focus() {
+                let value = self.store.reward_editor.value();
+                let value = value.trim();
+                if value.is_empty() {
+                    self.dispatch(actions::Action::UpdateStateNodeReward {
+                        node_idx,
+                        reward: None,
+                    });
+                } else if let Ok(reward) = value.parse::<f64>() {
+                    self.dispatch(actions::Action::UpdateStateNodeReward {
+                        node_idx,
+                        reward: Some(reward),
+                    });
+                }
+            }
+        });
+    }
+
     fn label_editor(
         &mut self,
         ui: &mut egui::Ui,
@@ -1777,6 +2389,47 @@ impl State {
         }
     }
 
+    fn passage_times_widget(
+        ui: &mut egui::Ui,
+        passage_times: &cache::PassageTimes,
+        node_idx: NodeIndex,
+    ) {
+        if let Some(return_time) = passage_times.return_times.get(&node_idx) {
+            ui.label(format!("Mean return time: {:.3}", return_time));
+        }
+
+        if let Some(times) = passage_times.first_passage.get(&node_idx) {
+            ui.label("Mean first-passage time:");
+            for (name, time) in times {
+                ui.label(format!("  ⏱ {} ({:.3})", name, time));
+            }
+        }
+    }
+
+    fn retrodiction_widget(
+        ui: &mut egui::Ui,
+        retrodiction: &cache::Retrodiction,
+        node_idx: NodeIndex,
+    ) {
+        let priors = [
+            ("initial weights", &retrodiction.initial),
+            ("equilibrium", &retrodiction.equilibrium),
+        ];
+        for (prior, posteriors) in priors {
+            ui.label(format!("Posterior states ({}):", prior));
+            match posteriors.get(&node_idx) {
+                Some(states) => {
+                    for (name, p) in states {
+                        ui.label(format!("  ← {} ({:.3})", name, p));
+                    }
+                }
+                None => {
+                    ui.label("  never observed");
+                }
+            }
+        }
+    }
+
     fn selection_widget(
         &mut self,
         ui: &mut egui::Ui,
@@ -2026,6 +2679,57 @@ impl State {
         }
     }
 
+    fn show_reversed_toggle(&mut self, ui: &mut egui::Ui, tab: ActiveTab, value: bool) {
+        let mut reversed = value;
+        if ui.checkbox(&mut reversed, "View reversed chain").changed() {
+            self.dispatch(actions::Action::SetShowReversed { tab, reversed });
+        }
+    }
+
+    fn value_coloring_controls(&mut self, ui: &mut egui::Ui) {
+        let mut show = self.store.state.show_values;
+        if ui
+            .checkbox(&mut show, "Color by value")
+            .on_hover_text("Discounted value of the node rewards")
+            .changed()
+        {
+            self.dispatch(actions::Action::SetShowValues { show });
+        }
+        if show {
+            let mut gamma = *self.store.state.discount.get();
+            if ui
+                .add(egui::Slider::new(&mut gamma, 0.0..=0.99).text("γ"))
+                .changed()
+            {
+                self.dispatch(actions::Action::SetDiscount { gamma });
+            }
+        }
+    }
+
+    // Heatmap of a derived kernel: hovering works, editing is disabled
+    fn show_read_only_heatmap(&mut self, ui: &mut egui::Ui, data: &heatmap::HeatmapData) {
+        let (x_labels, y_labels, matrix, x_node_indices, y_node_indices) = data;
+        let editing_state = heatmap::EditingState {
+            editing_cell: None,
+            edit_buffer: String::new(),
+        };
+
+        let (new_hover, _, _) = heatmap::show_heatmap(
+            ui,
+            x_labels,
+            y_labels,
+            matrix,
+            x_node_indices,
+            y_node_indices,
+            self.store.heatmap_hovered_cell,
+            editing_state,
+        );
+
+        if new_hover != self.store.heatmap_hovered_cell {
+            self.dispatch(actions::Action::SetHeatmapHoveredCell { cell: new_hover });
+        }
+    }
+
     fn reset_layout_for_tab(&self, ui: &mut egui::Ui, tab: ActiveTab) {
         match tab {
             ActiveTab::ObservableEditor => {
@@ -124,6 +124,9 @@ pub struct CircularNodeShape {
     dragged: bool,
     hovered: bool,
     color: Option<Color32>,
+    /// Fill used to group nodes, e.g. by communicating class
+    #[serde(default)]
+    group_color: Option<Color32>,
     label_text: String,
     radius: f64,
     label_font: f64,
@@ -138,6 +141,7 @@ impl<N: Clone> From<NodeProps<N>> for CircularNodeShape {
             dragged: props.dragged,
             hovered: props.hovered,
             color: props.color(),
+            group_color: None,
             label_text: props.label,
             radius: CIRCULAR_RADIUS,
             label_font: CIRCULAR_LABEL_FONT,
@@ -199,6 +203,10 @@ impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> DisplayNode<N, E, Ty, Ix>
 }
 
 impl CircularNodeShape {
+    pub fn set_group_color(&mut self, color: Option<Color32>) {
+        self.group_color = color;
+    }
+
     fn refresh_visuals(&mut self) {
         let visuals = circular_visuals();
         self.radius = visuals.radius;
@@ -211,7 +219,7 @@ impl CircularNodeShape {
     }
 
     fn effective_color(&self, ctx: &DrawContext) -> Color32 {
-        if let Some(c) = self.color {
+        if let Some(c) = self.color.or(self.group_color) {
             return c;
         }
         let visuals = if self.selected || self.dragged || self.hovered {
@@ -22,6 +22,8 @@ pub struct SerializableNode {
     name: String,
     #[serde(default = "default_weight")]
     weight: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reward: Option<f64>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -79,6 +81,7 @@ pub fn graph_to_serializable<
         nodes.push(SerializableNode {
             name: node.payload().name.clone(),
             weight: node.payload().weight,
+            reward: node.payload().reward,
         });
         node_index_map.insert(node_idx, new_idx);
     }
@@ -105,6 +108,7 @@ pub fn serializable_to_graph(state: &SerializableGraphState) -> StateGraph {
         let idx = g.add_node(StateNode {
             name: node.name.clone(),
             weight: node.weight,
+            reward: node.reward,
         });
         node_indices.push(idx);
     }
@@ -259,14 +263,17 @@ mod tests {
         let a = state_graph.add_node(crate::graph_state::StateNode {
             name: "A".to_string(),
             weight: 1.0,
+            reward: None,
         });
         let b = state_graph.add_node(crate::graph_state::StateNode {
             name: "B".to_string(),
             weight: 1.0,
+            reward: None,
         });
         let c = state_graph.add_node(crate::graph_state::StateNode {
             name: "C".to_string(),
             weight: 1.0,
+            reward: None,
         });
 
         // Add edges in state graph
@@ -1,5 +1,5 @@
 use crate::graph_state::{
-    HasName, ObservableNodeType, default_observable_graph, default_state_graph,
+    HasName, ObservableNodeType, SequencePosterior, default_observable_graph, default_state_graph,
 };
 use crate::graph_view;
 use crate::graph_view::{
@@ -159,6 +159,12 @@ pub struct StateGraphStore {
     pub graph: Versioned<StateGraphDisplay>,
     pub circular_visuals: Versioned<VisualParams>,
     pub label_visibility: Versioned<bool>,
+    /// Display the time-reversed chain instead of the graph
+    pub show_reversed: bool,
+    /// Colour nodes by the discounted value of their rewards
+    pub show_values: bool,
+    /// Discount factor of the value function
+    pub discount: Versioned<f64>,
     layout_reset: LayoutReset<StateVersionKey>,
 }
 
@@ -168,6 +174,9 @@ impl StateGraphStore {
             graph: Versioned::new(graph),
             circular_visuals: Versioned::new(VisualParams::default()),
             label_visibility: Versioned::new(true),
+            show_reversed: false,
+            show_values: false,
+            discount: Versioned::new(0.9),
             layout_reset: LayoutReset::new(),
         }
     }
@@ -240,6 +249,8 @@ impl ObservableGraphStore {
 pub struct ObservedGraphStore {
     pub circular_visuals: Versioned<VisualParams>,
     pub label_visibility: Versioned<bool>,
+    /// Display the time-reversed observed chain
+    pub show_reversed: bool,
     layout_reset: LayoutReset<ObservedVersionKey>,
 }
 
@@ -248,6 +259,7 @@ impl ObservedGraphStore {
         Self {
             circular_visuals: Versioned::new(VisualParams::default()),
             label_visibility: Versioned::new(true),
+            show_reversed: false,
             layout_reset: LayoutReset::new(),
         }
     }
@@ -270,6 +282,22 @@ impl ObservedGraphStore {
     }
 }
 
+/// Settings of the "Suggest observable…" dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SuggestObservableDialog {
+    pub macrostates: usize,
+    pub fuzzy: bool,
+}
+
+impl SuggestObservableDialog {
+    pub fn new() -> Self {
+        Self {
+            macrostates: 2,
+            fuzzy: false,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Store {
     // Graph-specific stores
@@ -292,9 +320,18 @@ pub struct Store {
 
     // Node editing state
     pub weight_editor: NumberEditor,
+    pub reward_editor: NumberEditor,
     pub label_editor: StringEditor,
     pub observed_node_selection: Option<(NodeIndex, bool)>,
 
+    // Open suggest observable dialog, if any
+    pub suggest_observable: Option<SuggestObservableDialog>,
+
+    // Observed sequence decoding
+    pub observed_sequence_input: String,
+    /// Cleared by the actions that edit what it was decoded from
+    pub sequence_posterior: Option<SequencePosterior>,
+
     // Global error state
     pub error_message: Option<String>,
 }
@@ -320,8 +357,12 @@ impl Store {
             heatmap_editing_cell: None,
             heatmap_edit_buffer: String::new(),
             weight_editor: NumberEditor::new(),
+            reward_editor: NumberEditor::new(),
             label_editor: StringEditor::new(),
             observed_node_selection: None,
+            suggest_observable: None,
+            observed_sequence_input: String::new(),
+            sequence_posterior: None,
             error_message: None,
         }
     }
@@ -358,7 +399,7 @@ impl Store {
     }
 }
 
-fn compute_generic_heatmap_data<N, D>(graph: &graph_view::GraphDisplay<N, D>) -> HeatmapData
+pub fn compute_generic_heatmap_data<N, D>(graph: &graph_view::GraphDisplay<N, D>) -> HeatmapData
 where
     N: Clone + HasName,
     D: DisplayNode<N, f64, Directed, DefaultIx>,
@@ -455,7 +496,7 @@ fn compute_observable_heatmap_data(graph: &ObservableGraphDisplay) -> HeatmapDat
     (x_labels, y_labels, matrix, x_node_indices, y_node_indices)
 }
 
-fn collect_sorted_weights_from_display<N, D>(graph: &graph_view::GraphDisplay<N, D>) -> Vec<f64>
+pub fn collect_sorted_weights_from_display<N, D>(graph: &graph_view::GraphDisplay<N, D>) -> Vec<f64>
 where
     N: Clone,
     D: DisplayNode<N, f64, Directed, DefaultIx>,
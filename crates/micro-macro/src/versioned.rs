@@ -18,6 +18,11 @@ impl<T> Versioned<T> {
         self.version = self.version.wrapping_add(1);
         &mut self.data
     }
+    /// Mutable access that keeps the version, for display-only state
+    /// (selection, colours, edge widths) that nothing keyed on it reads.
+    pub fn get_mut_untracked(&mut self) -> &mut T {
+        &mut self.data
+    }
     pub fn set(&mut self, data: T) {
         self.data = data;
         self.version = self.version.wrapping_add(1);